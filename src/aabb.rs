@@ -0,0 +1,128 @@
+use crate::interval::Interval;
+use crate::pos::Pos;
+use crate::ray::Ray;
+
+/// Axis-aligned bounding box
+#[derive(Debug, Copy, Clone)]
+pub struct Aabb {
+    pub x: Interval,
+    pub y: Interval,
+    pub z: Interval,
+}
+
+impl Aabb {
+    pub fn new(x: Interval, y: Interval, z: Interval) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn empty() -> Self {
+        Self::new(
+            Interval::empty(),
+            Interval::empty(),
+            Interval::empty(),
+        )
+    }
+
+    /// The box around objects that go on forever, such as planes
+    pub fn unbounded() -> Self {
+        Self::new(
+            Interval::maximal(),
+            Interval::maximal(),
+            Interval::maximal(),
+        )
+    }
+
+    pub fn is_unbounded(&self) -> bool {
+        (0..3).any(|axis| self.axis(axis).size() == f64::INFINITY)
+    }
+
+    /// The box spanning two opposite corners, given in any order
+    pub fn from_points(a: Pos, b: Pos) -> Self {
+        Self::new(
+            Interval::new(f64::min(a.x, b.x), f64::max(a.x, b.x)),
+            Interval::new(f64::min(a.y, b.y), f64::max(a.y, b.y)),
+            Interval::new(f64::min(a.z, b.z), f64::max(a.z, b.z)),
+        )
+    }
+
+    /// The smallest box containing both `a` and `b`
+    pub fn enclosing(a: &Aabb, b: &Aabb) -> Self {
+        Self::new(
+            Interval::enclosing(a.x, b.x),
+            Interval::enclosing(a.y, b.y),
+            Interval::enclosing(a.z, b.z),
+        )
+    }
+
+    /// Widen any axis that's too thin for the slab test to reliably hit, such as
+    /// the flat axis of a box around an axis-aligned triangle
+    pub fn padded(self) -> Self {
+        let delta = 0.0001;
+        let pad = |interval: Interval| if interval.size() < delta { interval.expand(delta) } else { interval };
+        Self::new(pad(self.x), pad(self.y), pad(self.z))
+    }
+
+    pub fn axis(&self, n: usize) -> Interval {
+        match n {
+            0 => self.x,
+            1 => self.y,
+            2 => self.z,
+            _ => panic!("Invalid Aabb axis: {n}"),
+        }
+    }
+
+    /// Index of the axis along which the box is widest
+    pub fn longest_axis(&self) -> usize {
+        if self.x.size() > self.y.size() {
+            if self.x.size() > self.z.size() { 0 } else { 2 }
+        }
+        else if self.y.size() > self.z.size() { 1 } else { 2 }
+    }
+
+    pub fn centroid(&self) -> Pos {
+        Pos::new(
+            (self.x.min + self.x.max) / 2.0,
+            (self.y.min + self.y.max) / 2.0,
+            (self.z.min + self.z.max) / 2.0,
+        )
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        // An empty box has negative extents, and no area
+        let dx = f64::max(self.x.size(), 0.0);
+        let dy = f64::max(self.y.size(), 0.0);
+        let dz = f64::max(self.z.size(), 0.0);
+        2.0 * ((dx * dy) + (dy * dz) + (dz * dx))
+    }
+
+    /// Slab test: does the ray pass through the box anywhere within `ray_t`?
+    pub fn hit(&self, ray: Ray, ray_t: Interval) -> bool {
+        self.hit_interval(ray, ray_t).is_some()
+    }
+
+    /// The part of `ray_t` for which the ray is inside the box, if any
+    pub fn hit_interval(&self, ray: Ray, mut ray_t: Interval) -> Option<Interval> {
+        for axis in 0..3 {
+            let slab = self.axis(axis);
+            let inverse_direction = 1.0 / ray.direction[axis];
+            let origin = ray.origin[axis];
+
+            let mut t0 = (slab.min - origin) * inverse_direction;
+            let mut t1 = (slab.max - origin) * inverse_direction;
+            if inverse_direction < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            if t0 > ray_t.min {
+                ray_t.min = t0;
+            }
+            if t1 < ray_t.max {
+                ray_t.max = t1;
+            }
+            if ray_t.max <= ray_t.min {
+                return None;
+            }
+        }
+        Some(ray_t)
+    }
+}
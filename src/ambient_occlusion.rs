@@ -0,0 +1,44 @@
+use crate::color::Color;
+use crate::integrator::{Integrator, SceneView};
+use crate::interval::Interval;
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+/// Shades the first surface each camera ray reaches by how open it is: the fraction of rays cast over its hemisphere
+/// that travel `max_distance` without hitting anything. Creases and corners come out dark, open surfaces light.
+/// Quick to render, and ignores materials and lighting entirely, so it's handy for previewing a scene's layout.
+pub struct AmbientOcclusionIntegrator {
+    samples: usize,
+    max_distance: f64,
+}
+
+impl AmbientOcclusionIntegrator {
+    pub fn new(samples: usize, max_distance: f64) -> Self {
+        Self {
+            samples,
+            max_distance,
+        }
+    }
+}
+
+impl Integrator for AmbientOcclusionIntegrator {
+    fn ray_color(&self, ray: Ray, scene: &SceneView) -> (Color, usize) {
+        let Some(hit_record) = scene.hit(ray) else {
+            // Nothing is in the way of a ray that escapes
+            return (Color::white(), 1);
+        };
+
+        // Cosine-weighted, so the rays closest to the normal count most, as they would for a diffuse surface
+        let basis = Onb::new(hit_record.normal);
+        let occlusion_t = Interval::new(0.001, self.max_distance);
+        let unoccluded_count = (0..self.samples).filter(|_| {
+            let local = Vec3::random_cosine_direction();
+            let direction = basis.local(local.x, local.y, local.z);
+            scene.world.hit(Ray::with_time(hit_record.pos, direction, ray.time), occlusion_t).is_none()
+        }).count();
+
+        let unoccluded_fraction = unoccluded_count as f64 / self.samples as f64;
+        (Color::new(unoccluded_fraction, unoccluded_fraction, unoccluded_fraction), 1 + self.samples)
+    }
+}
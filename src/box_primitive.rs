@@ -0,0 +1,51 @@
+use std::sync::Arc;
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::hittable_list::HittableList;
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::pos::Pos;
+use crate::quad::Quad;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+/// An axis-aligned box made of six outward-facing quads
+pub struct BoxPrimitive {
+    faces: HittableList,
+}
+
+impl BoxPrimitive {
+    /// The box spanning two opposite corners, given in any order.
+    /// Each face has its own material, in the order -X, +X, -Y, +Y, -Z, +Z.
+    pub fn with_face_materials(a: Pos, b: Pos, materials: [Arc<dyn Material>; 6]) -> Self {
+        let bounds = Aabb::from_points(a, b);
+        let min = Pos::new(bounds.x.min, bounds.y.min, bounds.z.min);
+        let max = Pos::new(bounds.x.max, bounds.y.max, bounds.z.max);
+        let dx = Vec3::new(bounds.x.size(), 0.0, 0.0);
+        let dy = Vec3::new(0.0, bounds.y.size(), 0.0);
+        let dz = Vec3::new(0.0, 0.0, bounds.z.size());
+
+        // Each quad's edges are ordered so that u x v points out of the box
+        let [neg_x, pos_x, neg_y, pos_y, neg_z, pos_z] = materials;
+        let mut faces = HittableList::new();
+        faces.add(Box::new(Quad::new(min, dz, dy, &neg_x)));
+        faces.add(Box::new(Quad::new(Pos::new(max.x, min.y, min.z), dy, dz, &pos_x)));
+        faces.add(Box::new(Quad::new(min, dx, dz, &neg_y)));
+        faces.add(Box::new(Quad::new(Pos::new(min.x, max.y, min.z), dz, dx, &pos_y)));
+        faces.add(Box::new(Quad::new(min, dy, dx, &neg_z)));
+        faces.add(Box::new(Quad::new(Pos::new(min.x, min.y, max.z), dx, dy, &pos_z)));
+        Self {
+            faces,
+        }
+    }
+}
+
+impl Hittable for BoxPrimitive {
+    fn hit(&self, ray: Ray, ray_t: Interval) -> Option<HitRecord> {
+        self.faces.hit(ray, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.faces.bounding_box()
+    }
+}
@@ -0,0 +1,199 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::hittable_list::HittableList;
+use crate::interval::Interval;
+use crate::ray::Ray;
+
+/// Bounding volume hierarchy over the objects of a `HittableList`.
+/// Rays only descend into children whose bounding box they pass through,
+/// so intersection cost grows with the depth of the tree rather than the object count.
+/// Unbounded objects, such as planes, are kept out of the tree and always tested.
+pub struct BvhNode {
+    left: Box<dyn Hittable>,
+    right: Option<Box<dyn Hittable>>,
+    bounding_box: Aabb,
+}
+
+impl BvhNode {
+    pub fn new(list: HittableList) -> Self {
+        let (mut objects, unbounded): (Vec<_>, Vec<_>) = list.into_objects()
+            .into_iter()
+            .partition(|object| !object.bounding_box().is_unbounded());
+        if !unbounded.is_empty() {
+            let mut unbounded_list = HittableList::new();
+            for object in unbounded {
+                unbounded_list.add(object);
+            }
+            let mut bounded_list = HittableList::new();
+            for object in objects {
+                bounded_list.add(object);
+            }
+            return Self {
+                left: Box::new(Self::new(bounded_list)),
+                right: Some(Box::new(unbounded_list)),
+                bounding_box: Aabb::unbounded(),
+            };
+        }
+
+        match objects.len() {
+            0 => Self {
+                left: Box::new(HittableList::new()),
+                right: None,
+                bounding_box: Aabb::empty(),
+            },
+            1 => {
+                let only = objects.remove(0);
+                Self {
+                    bounding_box: only.bounding_box(),
+                    left: only,
+                    right: None,
+                }
+            }
+            _ => Self::split(objects),
+        }
+    }
+
+    /// Build a subtree over the objects, which is just the object itself if there's only one
+    fn build(mut objects: Vec<Box<dyn Hittable>>) -> Box<dyn Hittable> {
+        if objects.len() == 1 {
+            objects.remove(0)
+        }
+        else {
+            Box::new(Self::split(objects))
+        }
+    }
+
+    /// Partition (at least two) objects in two using the surface area heuristic
+    fn split(mut objects: Vec<Box<dyn Hittable>>) -> Self {
+        // Split along the axis in which the object centers are most spread out
+        let centroid_bounds = objects.iter().fold(Aabb::empty(), |bounds, obj| {
+            let centroid = obj.bounding_box().centroid();
+            Aabb::enclosing(&bounds, &Aabb::from_points(centroid, centroid))
+        });
+        let axis = centroid_bounds.longest_axis();
+        objects.sort_by(|a, b| {
+            a.bounding_box().centroid()[axis].total_cmp(&b.bounding_box().centroid()[axis])
+        });
+
+        let split_index = if centroid_bounds.axis(axis).size() <= 0.0 {
+            // Every center coincides, so there's nothing to choose between. Split down the middle.
+            objects.len() / 2
+        }
+        else {
+            Self::cheapest_split_index(&objects)
+        };
+
+        let right_objects = objects.split_off(split_index);
+        let left = Self::build(objects);
+        let right = Self::build(right_objects);
+        Self {
+            bounding_box: Aabb::enclosing(&left.bounding_box(), &right.bounding_box()),
+            left,
+            right: Some(right),
+        }
+    }
+
+    /// Given objects sorted along the split axis, find the index that minimises
+    /// the expected cost of tracing a ray through both halves
+    fn cheapest_split_index(objects: &[Box<dyn Hittable>]) -> usize {
+        let count = objects.len();
+
+        // Surface area of the boxes enclosing objects[i..]
+        let mut suffix_areas = vec![0.0; count];
+        let mut suffix_bounds = Aabb::empty();
+        for i in (0..count).rev() {
+            suffix_bounds = Aabb::enclosing(&suffix_bounds, &objects[i].bounding_box());
+            suffix_areas[i] = suffix_bounds.surface_area();
+        }
+
+        let mut prefix_bounds = Aabb::empty();
+        let mut best_index = count / 2;
+        let mut best_cost = f64::MAX;
+        for i in 1..count {
+            prefix_bounds = Aabb::enclosing(&prefix_bounds, &objects[i - 1].bounding_box());
+            let cost = (prefix_bounds.surface_area() * i as f64) + (suffix_areas[i] * (count - i) as f64);
+            if cost < best_cost {
+                best_cost = cost;
+                best_index = i;
+            }
+        }
+        best_index
+    }
+}
+
+impl Hittable for BvhNode {
+    fn hit(&self, ray: Ray, ray_t: Interval) -> Option<HitRecord> {
+        if !self.bounding_box.hit(ray, ray_t) {
+            return None;
+        }
+
+        let left_hit = self.left.hit(ray, ray_t);
+        let Some(right) = &self.right else {
+            return left_hit;
+        };
+        // Only accept hits on the right that are closer than whatever we found on the left
+        let right_max = left_hit.as_ref().map_or(ray_t.max, |hit_record| hit_record.t);
+        match right.hit(ray, Interval::new(ray_t.min, right_max)) {
+            Some(right_hit) => Some(right_hit),
+            None => left_hit,
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use super::*;
+    use crate::color::Color;
+    use crate::lambertian::LambertianMaterial;
+    use crate::material::Material;
+    use crate::plane::Plane;
+    use crate::pos::Pos;
+    use crate::sphere::Sphere;
+    use crate::utils::{rand_double, seed_rng};
+    use crate::vec3::Vec3;
+
+    fn random_pos(extent: f64) -> Pos {
+        Pos::new(rand_double(-extent, extent), rand_double(-extent, extent), rand_double(-extent, extent))
+    }
+
+    #[test]
+    fn hits_match_a_linear_scan() {
+        seed_rng(1);
+        let material: Arc<dyn Material> = LambertianMaterial::new(Color::white());
+        let mut list = HittableList::new();
+        let mut bvh_list = HittableList::new();
+        for _ in 0..200 {
+            let sphere = Sphere::new(random_pos(10.0), rand_double(0.1, 1.5), &material);
+            list.add(Box::new(sphere.clone()));
+            bvh_list.add(Box::new(sphere));
+        }
+        // Unbounded objects are kept out of the tree, but must still be hit
+        list.add(Box::new(Plane::new(Pos::new(0.0, -12.0, 0.0), Vec3::new(0.0, 1.0, 0.0), &material)));
+        bvh_list.add(Box::new(Plane::new(Pos::new(0.0, -12.0, 0.0), Vec3::new(0.0, 1.0, 0.0), &material)));
+        let bvh = BvhNode::new(bvh_list);
+
+        let mut hit_count = 0;
+        for _ in 0..5000 {
+            let ray = Ray::new(random_pos(15.0), *random_pos(1.0));
+            let ray_t = Interval::new(0.001, f64::INFINITY);
+            let expected = list.hit(ray, ray_t).map(|hit_record| hit_record.t);
+            let actual = bvh.hit(ray, ray_t).map(|hit_record| hit_record.t);
+            assert_eq!(expected, actual, "ray from {:?} towards {:?}", ray.origin, ray.direction);
+            hit_count += expected.is_some() as usize;
+        }
+        // Make sure the rays didn't all miss
+        assert!(hit_count > 1000);
+    }
+
+    #[test]
+    fn empty_hierarchy_is_never_hit() {
+        let bvh = BvhNode::new(HittableList::new());
+        let ray = Ray::new(Pos::zero(), Vec3::new(0.0, 0.0, -1.0));
+        assert!(bvh.hit(ray, Interval::new(0.001, f64::INFINITY)).is_none());
+    }
+}
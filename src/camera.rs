@@ -1,17 +1,15 @@
-use std::fs::File;
-use std::io::Write;
 use std::sync::Arc;
-use std::time::SystemTime;
 use rayon::iter::IntoParallelIterator;
 use rayon::iter::ParallelIterator;
 use crate::color::Color;
-use crate::gradient::GradientMaterial;
-use crate::hittable::{HitRecord, Hittable};
-use crate::interval::Interval;
+use crate::framebuffer::Framebuffer;
+use crate::hittable::Hittable;
+use crate::integrator::{Integrator, SceneView};
+use crate::light::LightList;
 use crate::material::Material;
 use crate::pos::Pos;
 use crate::ray::Ray;
-use crate::utils::{degrees_to_radians, rand_proportion};
+use crate::utils::{degrees_to_radians, rand_proportion, seed_rng};
 use crate::vec3::Vec3;
 
 pub struct Camera {
@@ -19,7 +17,8 @@ pub struct Camera {
     image_height: usize,
     vertical_field_of_view_angle: f64,
     samples_per_pixel: usize,
-    max_ray_bounces: usize,
+    /// Works out the color seen along each sampled ray
+    integrator: Box<dyn Integrator>,
     camera_center: Pos,
     top_left_pixel_loc: Pos,
     /// Offset of pixel to the right
@@ -32,14 +31,22 @@ pub struct Camera {
     defocus_disk_u: Vec3,
     /// Defocus disk vertical radius
     defocus_disk_v: Vec3,
-    background_material: Arc<GradientMaterial>,
+    background_material: Arc<dyn Material>,
+    /// Emitters to aim rays at from each diffuse hit
+    lights: LightList,
+    /// When set, every scanline's random samples are derived from this,
+    /// so the same scene renders identically regardless of thread scheduling
+    seed: Option<u64>,
+    /// Rays are cast at times spread evenly over this interval
+    shutter_open: f64,
+    shutter_close: f64,
 }
 
 impl Camera {
     pub fn new(
         aspect_ratio: f64,
         image_width: usize,
-        background_material: Arc<GradientMaterial>,
+        background_material: Arc<dyn Material>,
         vertical_field_of_view_angle: f64,
         // Camera position in the world
         look_from: Pos,
@@ -51,7 +58,7 @@ impl Camera {
         // Distance from camera to plane of perfect focus
         focus_distance: f64,
         samples_per_pixel: usize,
-        max_ray_bounces: usize,
+        integrator: Box<dyn Integrator>,
     ) -> Self {
         let image_height = match (image_width as f64 / aspect_ratio) as usize {
             // Ensure the height is at least 1
@@ -93,7 +100,7 @@ impl Camera {
             image_height,
             vertical_field_of_view_angle,
             samples_per_pixel,
-            max_ray_bounces,
+            integrator,
             camera_center,
             top_left_pixel_loc,
             pixel_delta_u,
@@ -102,59 +109,77 @@ impl Camera {
             defocus_disk_u,
             defocus_disk_v,
             background_material,
+            lights: LightList::new(),
+            seed: None,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
         }
     }
 
-    pub fn render(&self, world: &dyn Hittable) -> std::io::Result<Vec<u8>> {
-        // Render
-        let mut out = vec![];
-        // Write out the PPM header
-        out.extend(format!("P3\n{} {}\n255\n", self.image_width, self.image_height).as_bytes());
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+
+    /// Sample these lights directly, rather than waiting for rays to stumble across them.
+    /// They must also be in the world that's rendered, to be seen and to cast shadows.
+    pub fn set_lights(&mut self, lights: LightList) {
+        self.lights = lights;
+    }
 
-        let mut output_lines = vec![vec![]; self.image_height];
+    /// Keep the shutter open from `open` until `close`, blurring anything that moves in between
+    pub fn set_shutter(&mut self, open: f64, close: f64) {
+        self.shutter_open = open;
+        self.shutter_close = close;
+    }
+
+    /// Render the world into an image, holding the average color of the samples taken through each pixel
+    pub fn render(&self, world: &dyn Hittable) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(self.image_width, self.image_height);
+        let scale = 1.0 / self.samples_per_pixel as f64;
+        let scene = SceneView {
+            world,
+            lights: &self.lights,
+            background: self.background_material.as_ref(),
+        };
 
         // Render each scanline in parallel
-        let scanline_indexes_to_pixel_bytes = (0..self.image_height).into_par_iter().map(|y|{
+        let rendered_scanlines = (0..self.image_height).into_par_iter().map(|y|{
             println!("Process scanline {}", self.image_height - y);
-            let mut scanline_bytes = vec![];
+            if let Some(seed) = self.seed {
+                // Mix the scanline index in so each scanline gets its own stream
+                seed_rng(seed ^ (y as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            }
+            let mut scanline = vec![];
+            let mut scanline_path_length = 0;
             for x in 0..self.image_width {
                 // Accumulate a pixel color through random sampling around the pixel
                 let mut pixel_color = Color::black();
                 for _sample in 0..self.samples_per_pixel {
                     let ray = self.get_ray(x, y);
-                    pixel_color += self.ray_color(ray, world, self.max_ray_bounces);
+                    let (sample_color, path_length) = self.integrator.ray_color(ray, &scene);
+                    pixel_color += sample_color;
+                    scanline_path_length += path_length;
                 }
-                self.write_color(&mut scanline_bytes, pixel_color);
+                scanline.push(scale * pixel_color);
             }
-            scanline_bytes.extend("\n".as_bytes());
-            (y, scanline_bytes)
-        }).collect::<Vec<(usize, Vec<u8>)>>();
+            (y, scanline, scanline_path_length)
+        }).collect::<Vec<(usize, Vec<Color>, usize)>>();
 
         // Order the rendered scanlines
-        for (y, scanline_bytes) in scanline_indexes_to_pixel_bytes.iter() {
-            output_lines[*y] = scanline_bytes.to_vec();
-        }
-        // And write them out
-        for line in output_lines.iter() {
-            out.extend(line)
+        let mut total_path_length = 0;
+        for (y, scanline, scanline_path_length) in rendered_scanlines.iter() {
+            framebuffer.row_mut(*y).copy_from_slice(scanline);
+            total_path_length += scanline_path_length;
         }
 
-        println!("Done! Writing output files...");
-
-        // Write to our output file
-        let mut output_file = File::create("./latest_image.ppm")?;
-        output_file.write(&out)?;
-
-        // Copy every output image to a secondary 'history' folder, so I can't accidentally forget to record progress
-        let dist_from_epoch = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).expect("Failed to retrieve time since epoch");
-        let mut cached_output_file = File::create(format!("./images/{}.ppm", dist_from_epoch.as_millis()))?;
-        cached_output_file.write(&out)?;
-
-        Ok(out)
+        println!("Done!");
+        let path_count = self.image_width * self.image_height * self.samples_per_pixel;
+        println!("Average path length: {:.2} rays", total_path_length as f64 / path_count as f64);
+        framebuffer
     }
 
     /// Get a randomly sampled camera ray for the pixel at (x, y),
-    /// originating from the camera defocus disk
+    /// originating from the camera defocus disk at a random time while the shutter is open
     fn get_ray(&self, x: usize, y: usize) -> Ray {
         let pixel_center = self.top_left_pixel_loc + (x as f64 * self.pixel_delta_u) + (y  as f64 * self.pixel_delta_v);
         let pixel_sample = pixel_center + self.pixel_sample_square();
@@ -165,7 +190,13 @@ impl Camera {
             self.defocus_disk_sample()
         };
         let ray_direction = *pixel_sample - *ray_origin;
-        Ray::new(ray_origin, ray_direction.into())
+        // Only draw a time when the shutter is open, so still scenes take the same samples as ever
+        let ray_time = if self.shutter_close > self.shutter_open {
+            self.shutter_open + (rand_proportion() * (self.shutter_close - self.shutter_open))
+        } else {
+            self.shutter_open
+        };
+        Ray::with_time(ray_origin, ray_direction, ray_time)
     }
 
     /// Randomly sample a point in the camera defocus disk
@@ -180,55 +211,4 @@ impl Camera {
         let py = -0.5 + rand_proportion();
         (px * self.pixel_delta_u) + (py * self.pixel_delta_v)
     }
-
-    fn linear_to_gamma(linear_component: f64) -> f64 {
-        linear_component.sqrt()
-    }
-
-    fn write_color(&self, out: &mut Vec<u8>, pixel_color: Color) {
-        let scale = 1.0 / self.samples_per_pixel as f64;
-        let scaled_color = Color::new(
-            pixel_color.r() * scale,
-            pixel_color.g() * scale,
-            pixel_color.b() * scale,
-        );
-
-        let corrected_color = Color::new(
-            Self::linear_to_gamma(scaled_color.r()),
-            Self::linear_to_gamma(scaled_color.g()),
-            Self::linear_to_gamma(scaled_color.b()),
-        );
-
-        let intensity = Interval::new(0.000, 0.999);
-        // TODO(PT): Refactor this into Color?
-        out.extend(
-            format!(
-                "{} {} {}    ",
-                (256. * intensity.clamp(corrected_color.r())).floor(),
-                (256. * intensity.clamp(corrected_color.g())).floor(),
-                (256. * intensity.clamp(corrected_color.b())).floor(),
-            ).as_bytes()
-        );
-    }
-
-    fn ray_color(&self, ray: Ray, world: &dyn Hittable, ray_bounces_remaining: usize) -> Color {
-        // If we've exceeded the ray bounce limit, no more light is contributed
-        if ray_bounces_remaining <= 0 {
-            Color::black()
-        }
-        // Don't allow intersections too close to this surface
-        else if let Some(hit_record) = world.hit(ray, Interval::new(0.001, f64::MAX)) {
-            if let Some((scattered_ray, color)) = hit_record.material.scatter(ray, Some(&hit_record)) {
-                color * self.ray_color(scattered_ray, world, ray_bounces_remaining - 1)
-            }
-            else {
-                Color::black()
-            }
-        }
-        else {
-            // Background
-            let (_, background_color) = self.background_material.scatter(ray, None).expect("Failed to get a ray color for the background");
-            background_color
-        }
-    }
-}
\ No newline at end of file
+}
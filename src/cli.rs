@@ -0,0 +1,105 @@
+use std::path::PathBuf;
+use clap::{Parser, ValueEnum};
+use crate::image_format::ImageFormat;
+use crate::integrator::IntegratorKind;
+
+#[derive(Parser)]
+#[command(about = "Render a scene with the ray tracer")]
+pub struct Cli {
+    /// Built-in scene to render
+    #[arg(long, value_enum, default_value_t = BuiltinScene::Pyramid, conflicts_with = "scene_file")]
+    pub scene: BuiltinScene,
+
+    /// Render the scene described by a JSON file instead of a built-in scene
+    #[arg(long, value_name = "PATH")]
+    pub scene_file: Option<PathBuf>,
+
+    /// Image width in pixels
+    #[arg(long)]
+    pub width: Option<usize>,
+
+    /// Width divided by height, either as a number or as `W:H`
+    #[arg(long, value_parser = parse_aspect_ratio)]
+    pub aspect_ratio: Option<f64>,
+
+    /// Rays traced through each pixel
+    #[arg(long, value_name = "COUNT")]
+    pub samples: Option<usize>,
+
+    /// How many times a ray may bounce before it stops contributing light
+    #[arg(long, value_name = "COUNT")]
+    pub max_bounces: Option<usize>,
+
+    /// How many times a ray bounces before dim paths may be ended at random.
+    /// Surviving paths are brightened to make up for the ones ended, so the image is unchanged on average.
+    #[arg(long, value_name = "COUNT")]
+    pub russian_roulette_depth: Option<usize>,
+
+    /// How light is simulated. Defaults to path tracing, unless the scene file picks another.
+    #[arg(long, value_enum)]
+    pub integrator: Option<IntegratorKind>,
+
+    /// Rays cast from each surface point by the ambient occlusion integrator
+    #[arg(long, value_name = "COUNT")]
+    pub ao_samples: Option<usize>,
+
+    /// How far away geometry can be and still darken a surface, for the ambient occlusion integrator
+    #[arg(long, value_name = "DISTANCE")]
+    pub ao_distance: Option<f64>,
+
+    /// Where to write the rendered image. Can be given several times to write
+    /// the same render in several formats, such as a PNG preview alongside an EXR.
+    #[arg(short, long = "output", value_name = "PATH", default_value = "latest_image.ppm")]
+    pub outputs: Vec<PathBuf>,
+
+    /// Output image format. Inferred from the output file extension if not given.
+    #[arg(long, value_enum)]
+    pub format: Option<ImageFormat>,
+
+    /// Also keep a timestamped copy of every rendered image in this directory
+    #[arg(long, value_name = "DIR")]
+    pub history_dir: Option<PathBuf>,
+
+    /// Number of render threads. Defaults to one per CPU.
+    #[arg(long)]
+    pub threads: Option<usize>,
+
+    /// Seed for every random choice made while building and rendering the scene,
+    /// so that renders can be reproduced exactly
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Render this many frames orbiting the scene instead of a single image.
+    /// Each frame is written next to the output path, numbered.
+    #[arg(long, value_name = "FRAMES")]
+    pub turntable: Option<usize>,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+pub enum BuiltinScene {
+    /// Diffuse, glass and metal balls on a yellow ground
+    ThreeBalls,
+    /// The cover image of Ray Tracing in One Weekend: hundreds of small random balls
+    Cover,
+    /// A pyramid of colored, glass and gold balls
+    Pyramid,
+    /// Balls lit only by glowing lamps, against a black background
+    Lamps,
+}
+
+fn parse_aspect_ratio(s: &str) -> Result<f64, String> {
+    let ratio = match s.split_once(':') {
+        Some((width, height)) => {
+            let width: f64 = width.trim().parse().map_err(|_| format!("invalid width in aspect ratio `{s}`"))?;
+            let height: f64 = height.trim().parse().map_err(|_| format!("invalid height in aspect ratio `{s}`"))?;
+            width / height
+        }
+        None => s.trim().parse().map_err(|_| format!("invalid aspect ratio `{s}`"))?,
+    };
+    if ratio.is_finite() && ratio > 0.0 {
+        Ok(ratio)
+    }
+    else {
+        Err(format!("aspect ratio `{s}` must be positive"))
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::Arc;
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::utils::rand_proportion;
+use crate::vec3::Vec3;
+
+/// A volume of uniform density filling a closed boundary, such as fog or smoke.
+/// Rays travelling through it scatter after an exponentially-distributed distance,
+/// so the denser the medium, the sooner they scatter.
+pub struct ConstantMedium {
+    boundary: Box<dyn Hittable>,
+    negative_inverse_density: f64,
+    /// Decides which way rays go when they scatter, such as an `IsotropicMaterial`
+    phase_function: Arc<dyn Material>,
+}
+
+impl ConstantMedium {
+    /// The boundary must be closed and convex, so that a ray enters and leaves it at most once
+    pub fn new(boundary: Box<dyn Hittable>, density: f64, phase_function: &Arc<dyn Material>) -> Self {
+        Self {
+            boundary,
+            negative_inverse_density: -1.0 / density,
+            phase_function: Arc::clone(phase_function),
+        }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, ray: Ray, ray_t: Interval) -> Option<HitRecord> {
+        // Find where the ray's line enters and leaves the boundary, even behind the ray's origin,
+        // so a ray starting inside the volume still finds its extent
+        let entry = self.boundary.hit(ray, Interval::maximal())?;
+        let exit = self.boundary.hit(ray, Interval::new(entry.t + 0.0001, f64::MAX))?;
+
+        let entry_t = f64::max(entry.t, f64::max(ray_t.min, 0.0));
+        let exit_t = f64::min(exit.t, ray_t.max);
+        if entry_t >= exit_t {
+            return None;
+        }
+
+        let ray_length = ray.direction.length();
+        let distance_inside_boundary = (exit_t - entry_t) * ray_length;
+        let hit_distance = self.negative_inverse_density * rand_proportion().ln();
+        if hit_distance > distance_inside_boundary {
+            return None;
+        }
+
+        let t = entry_t + (hit_distance / ray_length);
+        // There's no surface here, so the normal is arbitrary
+        Some(HitRecord::new(t, ray.at(t), ray, Vec3::new(1.0, 0.0, 0.0), &self.phase_function))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.boundary.bounding_box()
+    }
+}
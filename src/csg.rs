@@ -0,0 +1,214 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable, Span};
+use crate::interval::Interval;
+use crate::ray::Ray;
+
+/// How constructive solid geometry combines two solids
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CsgOperation {
+    /// Inside either solid
+    Union,
+    /// Inside both solids
+    Intersection,
+    /// Inside the first solid but not the second, carving the second out of the first
+    Difference,
+}
+
+impl CsgOperation {
+    fn contains(self, inside_left: bool, inside_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => inside_left || inside_right,
+            CsgOperation::Intersection => inside_left && inside_right,
+            CsgOperation::Difference => inside_left && !inside_right,
+        }
+    }
+}
+
+/// A solid built by combining two closed solids, such as a lens from two overlapping spheres.
+/// Both are traced along the whole ray, and their spans merged.
+pub struct Csg {
+    operation: CsgOperation,
+    left: Box<dyn Hittable>,
+    right: Box<dyn Hittable>,
+    bounding_box: Aabb,
+}
+
+/// Where the ray crosses the surface of one side of the combination
+struct Crossing {
+    t: f64,
+    is_right: bool,
+    entering: bool,
+    /// Missing at either end of a span that goes on forever
+    hit_record: Option<HitRecord>,
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, left: Box<dyn Hittable>, right: Box<dyn Hittable>) -> Self {
+        let (left_box, right_box) = (left.bounding_box(), right.bounding_box());
+        let bounding_box = match operation {
+            CsgOperation::Union => Aabb::enclosing(&left_box, &right_box),
+            CsgOperation::Intersection => {
+                let overlap = |a: Interval, b: Interval| Interval::new(a.min.max(b.min), a.max.min(b.max));
+                Aabb::new(
+                    overlap(left_box.x, right_box.x),
+                    overlap(left_box.y, right_box.y),
+                    overlap(left_box.z, right_box.z),
+                )
+            }
+            // Carving can only take away from the left
+            CsgOperation::Difference => left_box,
+        };
+        Self {
+            operation,
+            left,
+            right,
+            bounding_box,
+        }
+    }
+}
+
+impl Hittable for Csg {
+    fn hit(&self, ray: Ray, ray_t: Interval) -> Option<HitRecord> {
+        if !self.bounding_box.hit(ray, ray_t) {
+            return None;
+        }
+        self.hit_spans(ray)
+            .into_iter()
+            .flat_map(|span| [span.entry, span.exit])
+            .flatten()
+            .find(|hit_record| ray_t.surrounds(hit_record.t))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+
+    fn hit_spans(&self, ray: Ray) -> Vec<Span> {
+        let mut crossings = vec![];
+        for (is_right, side) in [(false, &self.left), (true, &self.right)] {
+            for span in side.hit_spans(ray) {
+                let entry_t = span.entry.as_ref().map_or(f64::NEG_INFINITY, |hit_record| hit_record.t);
+                let exit_t = span.exit.as_ref().map_or(f64::INFINITY, |hit_record| hit_record.t);
+                crossings.push(Crossing { t: entry_t, is_right, entering: true, hit_record: span.entry });
+                crossings.push(Crossing { t: exit_t, is_right, entering: false, hit_record: span.exit });
+            }
+        }
+        crossings.sort_by(|a, b| a.t.total_cmp(&b.t));
+
+        // Sweep along the ray, noting where being inside the combination changes
+        let mut spans = vec![];
+        let mut inside = [false, false];
+        let mut entry = None;
+        for crossing in crossings {
+            let was_inside = self.operation.contains(inside[0], inside[1]);
+            inside[crossing.is_right as usize] = crossing.entering;
+            let is_inside = self.operation.contains(inside[0], inside[1]);
+            if was_inside == is_inside {
+                continue;
+            }
+
+            let hit_record = crossing.hit_record.map(|mut hit_record| {
+                // The surface carved by the right of a difference faces into the right solid, out of the result.
+                // The normal already faces the ray, so only the side it was hit from flips.
+                if self.operation == CsgOperation::Difference && crossing.is_right {
+                    hit_record.is_front_face = !hit_record.is_front_face;
+                }
+                hit_record
+            });
+            if is_inside {
+                entry = Some(hit_record);
+            }
+            else {
+                spans.push(Span { entry: entry.take().flatten(), exit: hit_record });
+            }
+        }
+        spans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use super::*;
+    use crate::color::Color;
+    use crate::lambertian::LambertianMaterial;
+    use crate::material::Material;
+    use crate::pos::Pos;
+    use crate::sphere::Sphere;
+    use crate::vec3::Vec3;
+
+    fn sphere(center_x: f64, radius: f64) -> Box<dyn Hittable> {
+        let material: Arc<dyn Material> = LambertianMaterial::new(Color::white());
+        Box::new(Sphere::new(Pos::new(center_x, 0.0, 0.0), radius, &material))
+    }
+
+    /// A ray along the X axis, starting at x = -5
+    fn ray() -> Ray {
+        Ray::new(Pos::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0))
+    }
+
+    /// Where each span starts and ends along `ray()`, checking which way the surfaces face
+    fn span_ts(csg: &Csg) -> Vec<(f64, f64)> {
+        csg.hit_spans(ray()).into_iter().map(|span| {
+            let entry = span.entry.expect("spans should be closed");
+            let exit = span.exit.expect("spans should be closed");
+            assert!(entry.is_front_face, "span entered through a back face at {}", entry.t);
+            assert!(!exit.is_front_face, "span left through a front face at {}", exit.t);
+            (entry.t, exit.t)
+        }).collect()
+    }
+
+    fn assert_spans(csg: &Csg, expected: &[(f64, f64)]) {
+        let actual = span_ts(csg);
+        assert_eq!(actual.len(), expected.len(), "spans {actual:?}, expected {expected:?}");
+        for ((entry, exit), (expected_entry, expected_exit)) in actual.iter().zip(expected) {
+            assert!((entry - expected_entry).abs() < 1e-9 && (exit - expected_exit).abs() < 1e-9, "spans {actual:?}, expected {expected:?}");
+        }
+    }
+
+    // The overlapping spheres span x = -1..1 and 0..2, so t = 4..6 and 5..7 along the ray
+
+    #[test]
+    fn union_merges_overlapping_spans() {
+        assert_spans(&Csg::new(CsgOperation::Union, sphere(0.0, 1.0), sphere(1.0, 1.0)), &[(4.0, 7.0)]);
+    }
+
+    #[test]
+    fn union_keeps_separate_spans_apart() {
+        assert_spans(&Csg::new(CsgOperation::Union, sphere(0.0, 1.0), sphere(3.0, 1.0)), &[(4.0, 6.0), (7.0, 9.0)]);
+    }
+
+    #[test]
+    fn intersection_keeps_the_overlap() {
+        assert_spans(&Csg::new(CsgOperation::Intersection, sphere(0.0, 1.0), sphere(1.0, 1.0)), &[(5.0, 6.0)]);
+        assert_spans(&Csg::new(CsgOperation::Intersection, sphere(0.0, 1.0), sphere(3.0, 1.0)), &[]);
+    }
+
+    #[test]
+    fn difference_cuts_the_right_out_of_the_left() {
+        assert_spans(&Csg::new(CsgOperation::Difference, sphere(0.0, 1.0), sphere(1.0, 1.0)), &[(4.0, 5.0)]);
+        assert_spans(&Csg::new(CsgOperation::Difference, sphere(1.0, 1.0), sphere(0.0, 1.0)), &[(6.0, 7.0)]);
+    }
+
+    #[test]
+    fn difference_can_hollow_out_a_solid() {
+        let shell = Csg::new(CsgOperation::Difference, sphere(0.0, 2.0), sphere(0.0, 1.0));
+        assert_spans(&shell, &[(3.0, 4.0), (6.0, 7.0)]);
+
+        // From inside the cavity, the first surface is the inside of the shell, which is entered
+        let ray = Ray::new(Pos::zero(), Vec3::new(1.0, 0.0, 0.0));
+        let hit_record = shell.hit(ray, Interval::new(0.001, f64::INFINITY)).unwrap();
+        assert!((hit_record.t - 1.0).abs() < 1e-9);
+        assert!(hit_record.is_front_face);
+        assert_eq!(hit_record.normal, Vec3::new(-1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn hit_only_reports_surfaces_within_the_interval() {
+        let union = Csg::new(CsgOperation::Union, sphere(0.0, 1.0), sphere(1.0, 1.0));
+        // The spheres' surfaces inside the union aren't part of it
+        let hit_record = union.hit(ray(), Interval::new(4.5, f64::INFINITY)).unwrap();
+        assert!((hit_record.t - 7.0).abs() < 1e-9);
+        assert!(union.hit(ray(), Interval::new(7.5, f64::INFINITY)).is_none());
+    }
+}
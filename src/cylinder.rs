@@ -0,0 +1,206 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::onb::Onb;
+use crate::pos::Pos;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+/// A solid of revolution whose radius changes linearly from its base to its top,
+/// covering cylinders (equal radii) and cones (a zero radius) alike
+struct Frustum {
+    base: Pos,
+    /// `w` runs along the axis, from base to top
+    basis: Onb,
+    height: f64,
+    base_radius: f64,
+    top_radius: f64,
+    capped: bool,
+    material: Arc<dyn Material>,
+    bounding_box: Aabb,
+}
+
+impl Frustum {
+    fn new(
+        base: Pos,
+        top: Pos,
+        base_radius: f64,
+        top_radius: f64,
+        capped: bool,
+        material: &Arc<dyn Material>,
+    ) -> Self {
+        let axis = top - *base;
+        let basis = Onb::new(*axis);
+        // Bound the circles at each end, which reach less far along the axes the frustum leans towards
+        let end_box = |center: Pos, radius: f64| {
+            let extent = |axis: usize| radius * (1.0 - (basis.w[axis] * basis.w[axis])).max(0.0).sqrt();
+            let extent = Vec3::new(extent(0), extent(1), extent(2));
+            Aabb::from_points(center - extent, center + extent)
+        };
+        Self {
+            base,
+            basis,
+            height: axis.length(),
+            base_radius,
+            top_radius,
+            capped,
+            material: Arc::clone(material),
+            bounding_box: Aabb::enclosing(&end_box(base, base_radius), &end_box(top, top_radius)).padded(),
+        }
+    }
+
+    /// Radius at height `z` along the axis
+    fn radius_at(&self, z: f64) -> f64 {
+        self.base_radius + ((self.top_radius - self.base_radius) * (z / self.height))
+    }
+
+    /// The nearest hit on the curved side, as the ray parameter and the local outward normal and hit point
+    fn hit_side(&self, origin: Vec3, direction: Vec3, ray_t: Interval) -> Option<(f64, Vec3, Vec3)> {
+        // Points on the side satisfy x^2 + y^2 = radius_at(z)^2
+        let slope = (self.top_radius - self.base_radius) / self.height;
+        let origin_radius = self.base_radius + (slope * origin.z);
+        let a = (direction.x * direction.x) + (direction.y * direction.y) - (slope * slope * direction.z * direction.z);
+        let half_b = (origin.x * direction.x) + (origin.y * direction.y) - (slope * direction.z * origin_radius);
+        let c = (origin.x * origin.x) + (origin.y * origin.y) - (origin_radius * origin_radius);
+
+        let roots = if a.abs() < 1e-12 {
+            // Parallel to the side of a cone, so it only crosses it once
+            if half_b.abs() < 1e-12 {
+                return None;
+            }
+            [-c / (2.0 * half_b), f64::NAN]
+        }
+        else {
+            let discriminant = (half_b * half_b) - (a * c);
+            if discriminant < 0.0 {
+                return None;
+            }
+            let sqrtd = discriminant.sqrt();
+            let (near, far) = ((-half_b - sqrtd) / a, (-half_b + sqrtd) / a);
+            [f64::min(near, far), f64::max(near, far)]
+        };
+
+        roots.into_iter()
+            .filter(|t| ray_t.surrounds(*t))
+            .map(|t| (t, origin + (t * direction)))
+            // Only the part between the ends, and not the mirrored cone beyond a tip
+            .find(|(_, p)| (0.0..=self.height).contains(&p.z))
+            .map(|(t, p)| {
+                let normal = Vec3::new(p.x, p.y, -self.radius_at(p.z) * slope);
+                // Right at a cone's tip, any direction along the axis will do
+                let normal = if normal.is_near_zero() { Vec3::new(0.0, 0.0, 1.0) } else { normal.unit_vector() };
+                (t, normal, p)
+            })
+    }
+
+    /// The nearest hit on an end cap at height `z`
+    fn hit_cap(&self, origin: Vec3, direction: Vec3, ray_t: Interval, z: f64, radius: f64) -> Option<(f64, Vec3, Vec3)> {
+        if radius <= 0.0 || direction.z.abs() < 1e-12 {
+            return None;
+        }
+        let t = (z - origin.z) / direction.z;
+        if !ray_t.surrounds(t) {
+            return None;
+        }
+        let p = origin + (t * direction);
+        if (p.x * p.x) + (p.y * p.y) > radius * radius {
+            return None;
+        }
+        let normal = if z > 0.0 { Vec3::new(0.0, 0.0, 1.0) } else { Vec3::new(0.0, 0.0, -1.0) };
+        Some((t, normal, p))
+    }
+}
+
+impl Hittable for Frustum {
+    fn hit(&self, ray: Ray, ray_t: Interval) -> Option<HitRecord> {
+        // Work in the frustum's own frame, with the base at the origin and the axis along Z.
+        // The basis is orthonormal, so `t` is the same in both frames.
+        let origin = self.basis.coordinates(*(ray.origin - *self.base));
+        let direction = self.basis.coordinates(ray.direction);
+
+        let mut nearest = self.hit_side(origin, direction, ray_t);
+        if self.capped {
+            for (z, radius) in [(0.0, self.base_radius), (self.height, self.top_radius)] {
+                let max = nearest.map_or(ray_t.max, |(t, _, _)| t);
+                if let Some(cap_hit) = self.hit_cap(origin, direction, Interval::new(ray_t.min, max), z, radius) {
+                    nearest = Some(cap_hit);
+                }
+            }
+        }
+
+        let (t, normal, p) = nearest?;
+        let outward_normal = self.basis.local(normal.x, normal.y, normal.z);
+        // Around the axis, then up from the base
+        let u = (f64::atan2(p.y, p.x) + PI) / (2.0 * PI);
+        let v = p.z / self.height;
+        Some(HitRecord::new(t, ray.at(t), ray, outward_normal, &self.material).with_uv(u, v))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+}
+
+/// A cylinder running from the center of its base to the center of its top.
+/// Without caps, it's an open tube.
+pub struct Cylinder {
+    frustum: Frustum,
+}
+
+impl Cylinder {
+    pub fn new(
+        base: Pos,
+        top: Pos,
+        radius: f64,
+        capped: bool,
+        material: &Arc<dyn Material>,
+    ) -> Self {
+        Self {
+            frustum: Frustum::new(base, top, radius, radius, capped, material),
+        }
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, ray: Ray, ray_t: Interval) -> Option<HitRecord> {
+        self.frustum.hit(ray, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.frustum.bounding_box()
+    }
+}
+
+/// A cone, or with a nonzero top radius a truncated cone, running from the center of its base to the center of its top.
+/// Caps close off whichever ends have a nonzero radius.
+pub struct Cone {
+    frustum: Frustum,
+}
+
+impl Cone {
+    pub fn new(
+        base: Pos,
+        top: Pos,
+        base_radius: f64,
+        top_radius: f64,
+        capped: bool,
+        material: &Arc<dyn Material>,
+    ) -> Self {
+        Self {
+            frustum: Frustum::new(base, top, base_radius, top_radius, capped, material),
+        }
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, ray: Ray, ray_t: Interval) -> Option<HitRecord> {
+        self.frustum.hit(ray, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.frustum.bounding_box()
+    }
+}
@@ -0,0 +1,17 @@
+use crate::color::Color;
+use crate::integrator::{Integrator, SceneView};
+use crate::ray::Ray;
+
+/// Colors each pixel by the outward normal of the first surface seen, mapping each axis from -1..1 to 0..1.
+/// Useful for checking geometry, since it ignores materials and lighting entirely.
+pub struct DebugIntegrator;
+
+impl Integrator for DebugIntegrator {
+    fn ray_color(&self, ray: Ray, scene: &SceneView) -> (Color, usize) {
+        let Some(hit_record) = scene.hit(ray) else {
+            return (Color::black(), 1);
+        };
+        let outward_normal = if hit_record.is_front_face { hit_record.normal } else { -hit_record.normal };
+        (0.5 * Color::new(outward_normal.x + 1.0, outward_normal.y + 1.0, outward_normal.z + 1.0), 1)
+    }
+}
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 use crate::color::Color;
 use crate::hittable::HitRecord;
-use crate::material::Material;
+use crate::material::{Material, ScatterRecord};
 use crate::ray::Ray;
 use crate::utils::rand_proportion;
 use crate::vec3::Vec3;
@@ -24,12 +24,11 @@ fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
     // Schlick's approximation for reflectance
     let mut r0 = (1.0 - ref_idx) / (1.0 + ref_idx);
     r0 = r0 * r0;
-    r0 + (1.0 - r0) * f64::powf((1.0 - cosine), 5.0)
+    r0 + (1.0 - r0) * f64::powf(1.0 - cosine, 5.0)
 }
 
 impl Material for DielectricMaterial {
-    fn scatter(&self, ray: Ray, hit_record: Option<&HitRecord>) -> Option<(Ray, Color)> {
-        let hit_record = hit_record.expect("Expected a hit record to be available");
+    fn scatter(&self, ray: Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
         let attenuation = Color::white();
         let refraction_ratio = match hit_record.is_front_face {
             true => 1.0 / self.index_of_refraction,
@@ -48,6 +47,6 @@ impl Material for DielectricMaterial {
             Vec3::refract(unit_direction, hit_record.normal, refraction_ratio)
         };
 
-        Some((Ray::new(hit_record.pos, direction), attenuation))
+        Some(ScatterRecord::delta(Ray::with_time(hit_record.pos, direction, ray.time), attenuation))
     }
 }
@@ -0,0 +1,30 @@
+use std::sync::Arc;
+use crate::color::Color;
+use crate::hittable::HitRecord;
+use crate::material::{Material, ScatterRecord};
+use crate::ray::Ray;
+
+/// A surface that glows evenly in every direction, and doesn't reflect anything
+pub struct DiffuseLightMaterial {
+    emit: Color,
+}
+
+impl DiffuseLightMaterial {
+    pub fn new(emit: Color) -> Arc<Self> {
+        Arc::new(
+            Self {
+                emit,
+            }
+        )
+    }
+}
+
+impl Material for DiffuseLightMaterial {
+    fn scatter(&self, _ray: Ray, _hit_record: &HitRecord) -> Option<ScatterRecord> {
+        None
+    }
+
+    fn emitted(&self, _hit_record: &HitRecord) -> Color {
+        self.emit
+    }
+}
@@ -0,0 +1,51 @@
+use crate::color::Color;
+use crate::integrator::{emitted_light, sample_lights, Integrator, SceneView};
+use crate::ray::Ray;
+
+/// Lights the first diffuse surface each camera ray reaches, straight from the lights and the background, without any bounced light.
+/// Rays carry on through mirrors and glass, so they still show what's reflected and refracted.
+pub struct DirectLightingIntegrator {
+    max_ray_bounces: usize,
+}
+
+impl DirectLightingIntegrator {
+    pub fn new(max_ray_bounces: usize) -> Self {
+        Self {
+            max_ray_bounces,
+        }
+    }
+}
+
+impl Integrator for DirectLightingIntegrator {
+    fn ray_color(&self, ray: Ray, scene: &SceneView) -> (Color, usize) {
+        let mut color = Color::black();
+        let mut throughput = Color::white();
+        let mut ray = ray;
+        let mut bsdf_pdf = None;
+        let mut path_length = 0;
+
+        while path_length < self.max_ray_bounces {
+            path_length += 1;
+            let Some(hit_record) = scene.hit(ray) else {
+                color += throughput * scene.background.background(ray);
+                break;
+            };
+            color += throughput * emitted_light(scene, ray, &hit_record, bsdf_pdf);
+            // This ray was the BSDF's half of lighting a diffuse surface, so any light it found is all that's wanted
+            if bsdf_pdf.is_some() {
+                break;
+            }
+
+            let Some(scatter) = hit_record.material.scatter(ray, &hit_record) else {
+                break;
+            };
+            if !scatter.is_delta {
+                color += throughput * sample_lights(scene, ray, &hit_record);
+                bsdf_pdf = Some(scatter.pdf);
+            }
+            throughput = throughput * scatter.weight();
+            ray = scatter.ray;
+        }
+        (color, path_length)
+    }
+}
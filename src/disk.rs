@@ -0,0 +1,67 @@
+use std::sync::Arc;
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::onb::Onb;
+use crate::pos::Pos;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+/// A flat circle, whose front face is the side `normal` points towards.
+/// Surface coordinates map the square around the disk to [0, 1].
+pub struct Disk {
+    center: Pos,
+    radius: f64,
+    basis: Onb,
+    material: Arc<dyn Material>,
+    bounding_box: Aabb,
+}
+
+impl Disk {
+    pub fn new(
+        center: Pos,
+        normal: Vec3,
+        radius: f64,
+        material: &Arc<dyn Material>,
+    ) -> Self {
+        let basis = Onb::new(normal);
+        // The disk reaches less far along the axes its normal leans towards
+        let extent = |axis: usize| radius * (1.0 - (basis.w[axis] * basis.w[axis])).max(0.0).sqrt();
+        let extent = Vec3::new(extent(0), extent(1), extent(2));
+        Self {
+            center,
+            radius,
+            basis,
+            material: Arc::clone(material),
+            bounding_box: Aabb::from_points(center - extent, center + extent).padded(),
+        }
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, ray: Ray, ray_t: Interval) -> Option<HitRecord> {
+        let denominator = Vec3::dot(self.basis.w, ray.direction);
+        // The ray is parallel to the disk
+        if denominator.abs() < 1e-8 {
+            return None;
+        }
+        let t = Vec3::dot(self.basis.w, *self.center - *ray.origin) / denominator;
+        if !ray_t.surrounds(t) {
+            return None;
+        }
+
+        let hit_pos = ray.at(t);
+        let offset = *hit_pos - *self.center;
+        if offset.length_squared() > self.radius * self.radius {
+            return None;
+        }
+        let u = 0.5 + (Vec3::dot(offset, self.basis.u) / (2.0 * self.radius));
+        let v = 0.5 + (Vec3::dot(offset, self.basis.v) / (2.0 * self.radius));
+        Some(HitRecord::new(t, hit_pos, ray, self.basis.w, &self.material).with_uv(u, v))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+}
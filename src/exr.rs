@@ -0,0 +1,154 @@
+use crate::framebuffer::Framebuffer;
+
+const PIXEL_TYPE_FLOAT: i32 = 2;
+const NO_COMPRESSION: u8 = 0;
+const INCREASING_Y: u8 = 0;
+
+/// OpenEXR holding uncompressed 32-bit float R, G and B channels, one scanline per chunk
+pub fn encode_exr(framebuffer: &Framebuffer) -> Vec<u8> {
+    let width = framebuffer.width();
+    let height = framebuffer.height();
+    let mut out = vec![];
+
+    // Magic number, then version 2 with no feature flags: a single-part scanline image
+    out.extend([0x76, 0x2f, 0x31, 0x01]);
+    out.extend(2i32.to_le_bytes());
+
+    // Channels must be listed in alphabetical order
+    let channel_names = ["B", "G", "R"];
+    let mut channels = vec![];
+    for name in channel_names {
+        channels.extend(name.as_bytes());
+        channels.push(0);
+        channels.extend(PIXEL_TYPE_FLOAT.to_le_bytes());
+        // pLinear, and three reserved bytes
+        channels.extend([0, 0, 0, 0]);
+        // x and y sampling
+        channels.extend(1i32.to_le_bytes());
+        channels.extend(1i32.to_le_bytes());
+    }
+    channels.push(0);
+    write_attribute(&mut out, "channels", "chlist", &channels);
+
+    write_attribute(&mut out, "compression", "compression", &[NO_COMPRESSION]);
+    let mut window = vec![];
+    for bound in [0, 0, width as i32 - 1, height as i32 - 1] {
+        window.extend(bound.to_le_bytes());
+    }
+    write_attribute(&mut out, "dataWindow", "box2i", &window);
+    write_attribute(&mut out, "displayWindow", "box2i", &window);
+    write_attribute(&mut out, "lineOrder", "lineOrder", &[INCREASING_Y]);
+    write_attribute(&mut out, "pixelAspectRatio", "float", &1f32.to_le_bytes());
+    write_attribute(&mut out, "screenWindowCenter", "v2f", &[0f32.to_le_bytes(), 0f32.to_le_bytes()].concat());
+    write_attribute(&mut out, "screenWindowWidth", "float", &1f32.to_le_bytes());
+    // End of header
+    out.push(0);
+
+    // Offset table, pointing at each scanline chunk
+    let scanline_data_size = width * channel_names.len() * size_of::<f32>();
+    let chunk_size = (2 * size_of::<i32>()) + scanline_data_size;
+    let first_chunk_offset = out.len() + (height * size_of::<u64>());
+    for y in 0..height {
+        out.extend(((first_chunk_offset + (y * chunk_size)) as u64).to_le_bytes());
+    }
+
+    for y in 0..height {
+        out.extend((y as i32).to_le_bytes());
+        out.extend((scanline_data_size as i32).to_le_bytes());
+        let row = framebuffer.row(y);
+        // Each channel's samples for the whole scanline are stored together, in the same order as the channel list
+        for component in [2, 1, 0] {
+            for pixel in row {
+                out.extend((pixel[component] as f32).to_le_bytes());
+            }
+        }
+    }
+    out
+}
+
+fn write_attribute(out: &mut Vec<u8>, name: &str, attribute_type: &str, value: &[u8]) {
+    out.extend(name.as_bytes());
+    out.push(0);
+    out.extend(attribute_type.as_bytes());
+    out.push(0);
+    out.extend((value.len() as i32).to_le_bytes());
+    out.extend(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn read_i32(bytes: &[u8], offset: usize) -> i32 {
+        i32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn read_string(bytes: &[u8], offset: &mut usize) -> String {
+        let end = *offset + bytes[*offset..].iter().position(|byte| *byte == 0).unwrap();
+        let string = String::from_utf8(bytes[*offset..end].to_vec()).unwrap();
+        *offset = end + 1;
+        string
+    }
+
+    /// Reads back the subset of OpenEXR that `encode_exr` writes, following the offset table to each scanline
+    fn decode_exr(bytes: &[u8]) -> Framebuffer {
+        assert_eq!(&bytes[0..4], &[0x76, 0x2f, 0x31, 0x01]);
+        assert_eq!(read_i32(bytes, 4), 2);
+
+        let mut offset = 8;
+        let mut attributes = vec![];
+        while bytes[offset] != 0 {
+            let name = read_string(bytes, &mut offset);
+            let attribute_type = read_string(bytes, &mut offset);
+            let size = read_i32(bytes, offset) as usize;
+            attributes.push((name, attribute_type, bytes[offset + 4..offset + 4 + size].to_vec()));
+            offset += 4 + size;
+        }
+        offset += 1;
+
+        let attribute = |name: &str| &attributes.iter().find(|attribute| attribute.0 == name).unwrap().2;
+        assert_eq!(attribute("compression"), &vec![NO_COMPRESSION]);
+        let window = attribute("dataWindow");
+        let (min_x, min_y) = (read_i32(window, 0), read_i32(window, 4));
+        let (max_x, max_y) = (read_i32(window, 8), read_i32(window, 12));
+        let width = (max_x - min_x + 1) as usize;
+        let height = (max_y - min_y + 1) as usize;
+
+        let mut framebuffer = Framebuffer::new(width, height);
+        for line in 0..height {
+            let chunk_offset = u64::from_le_bytes(bytes[offset + (line * 8)..offset + (line * 8) + 8].try_into().unwrap()) as usize;
+            let y = read_i32(bytes, chunk_offset) as usize;
+            assert_eq!(read_i32(bytes, chunk_offset + 4) as usize, width * 3 * 4);
+            let sample = |channel: usize, x: usize| {
+                let at = chunk_offset + 8 + (((channel * width) + x) * 4);
+                f32::from_le_bytes(bytes[at..at + 4].try_into().unwrap()) as f64
+            };
+            // Channels are stored in alphabetical order: B, G, R
+            for x in 0..width {
+                framebuffer.row_mut(y)[x] = Color::new(sample(2, x), sample(1, x), sample(0, x));
+            }
+        }
+        framebuffer
+    }
+
+    #[test]
+    fn round_trips_through_uncompressed_scanlines() {
+        let (width, height) = (5, 4);
+        let mut framebuffer = Framebuffer::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                framebuffer.row_mut(y)[x] = Color::new(x as f64 / 3.0, 100.0 * y as f64, (x * y) as f64 - 2.5);
+            }
+        }
+        let decoded = decode_exr(&encode_exr(&framebuffer));
+        assert_eq!((decoded.width(), decoded.height()), (width, height));
+        for y in 0..height {
+            for (expected, actual) in framebuffer.row(y).iter().zip(decoded.row(y)) {
+                for component in 0..3 {
+                    assert_eq!(expected[component] as f32 as f64, actual[component]);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use crate::color::Color;
+
+/// A rendered image, holding the average linear radiance seen through each pixel.
+/// Nothing is gamma-corrected or clamped, that's left to whatever encodes the image.
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    /// Row-major, starting from the top-left pixel
+    pixels: Vec<Color>,
+}
+
+impl Framebuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Color::black(); width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn row(&self, y: usize) -> &[Color] {
+        &self.pixels[(y * self.width)..((y + 1) * self.width)]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [Color] {
+        &mut self.pixels[(y * self.width)..((y + 1) * self.width)]
+    }
+}
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 use crate::color::Color;
 use crate::hittable::HitRecord;
-use crate::material::Material;
+use crate::material::{Material, ScatterRecord};
 use crate::ray::Ray;
 
 pub struct GradientMaterial {
@@ -24,9 +24,13 @@ impl GradientMaterial {
 }
 
 impl Material for GradientMaterial {
-    fn scatter(&self, ray: Ray, _hit_record: Option<&HitRecord>) -> Option<(Ray, Color)> {
+    fn scatter(&self, _ray: Ray, _hit_record: &HitRecord) -> Option<ScatterRecord> {
+        None
+    }
+
+    fn background(&self, ray: Ray) -> Color {
         let unit_direction = ray.direction().unit_vector();
         let a = unit_direction.y + 1.0;
-        Some((Ray::zero(), ((1.0 - a) * self.to_color) + (a * self.from_color)))
+        ((1.0 - a) * self.to_color) + (a * self.from_color)
     }
 }
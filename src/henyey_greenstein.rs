@@ -0,0 +1,74 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+use crate::color::Color;
+use crate::hittable::HitRecord;
+use crate::material::{Material, ScatterRecord};
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::texture::Texture;
+use crate::utils::rand_proportion;
+use crate::vec3::Vec3;
+
+/// Phase function for participating media that favour scattering forwards or backwards, like clouds and smoke.
+/// Media such as fire can also glow.
+pub struct HenyeyGreensteinMaterial {
+    albedo: Arc<dyn Texture>,
+    /// Mean cosine of the scattering angle: positive scatters mostly forwards, negative mostly backwards,
+    /// and zero in every direction equally
+    asymmetry: f64,
+    emit: Color,
+}
+
+impl HenyeyGreensteinMaterial {
+    pub fn new(albedo: Arc<dyn Texture>, asymmetry: f64, emit: Color) -> Arc<Self> {
+        Arc::new(
+            Self {
+                albedo,
+                asymmetry: asymmetry.clamp(-0.99, 0.99),
+                emit,
+            }
+        )
+    }
+
+    /// Density of scattering through an angle with this cosine, which sampling follows exactly
+    fn phase(&self, cos_theta: f64) -> f64 {
+        let g = self.asymmetry;
+        let denominator = 1.0 + (g * g) - (2.0 * g * cos_theta);
+        (1.0 - (g * g)) / (4.0 * PI * denominator * denominator.sqrt())
+    }
+
+    /// Cosine of the angle between the incoming and scattered directions, drawn from the phase function
+    fn sample_cos_theta(&self) -> f64 {
+        let g = self.asymmetry;
+        let xi = rand_proportion();
+        if g.abs() < 1e-3 {
+            return 1.0 - (2.0 * xi);
+        }
+        let s = (1.0 - (g * g)) / (1.0 - g + (2.0 * g * xi));
+        ((1.0 + (g * g) - (s * s)) / (2.0 * g)).clamp(-1.0, 1.0)
+    }
+}
+
+impl Material for HenyeyGreensteinMaterial {
+    fn scatter(&self, ray: Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let cos_theta = self.sample_cos_theta();
+        let sin_theta = (1.0 - (cos_theta * cos_theta)).max(0.0).sqrt();
+        let phi = 2.0 * PI * rand_proportion();
+        let direction = Onb::new(ray.direction).local(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta);
+        let scattered = Ray::with_time(hit_record.pos, direction, ray.time);
+        let pdf = self.phase(cos_theta);
+        Some(ScatterRecord::new(scattered, pdf * self.albedo.value(hit_record.u, hit_record.v, hit_record.pos), pdf))
+    }
+
+    fn eval(&self, ray: Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        self.pdf(ray, hit_record, direction) * self.albedo.value(hit_record.u, hit_record.v, hit_record.pos)
+    }
+
+    fn pdf(&self, ray: Ray, _hit_record: &HitRecord, direction: Vec3) -> f64 {
+        self.phase(Vec3::dot(ray.direction.unit_vector(), direction.unit_vector()))
+    }
+
+    fn emitted(&self, _hit_record: &HitRecord) -> Color {
+        self.emit
+    }
+}
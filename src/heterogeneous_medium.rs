@@ -0,0 +1,75 @@
+use std::sync::Arc;
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::pos::Pos;
+use crate::ray::Ray;
+use crate::utils::rand_proportion;
+use crate::vec3::Vec3;
+use crate::voxel_grid::VoxelGrid;
+
+/// A volume whose density varies through a box, following a voxel grid, such as a cloud or a smoke simulation.
+/// Rays travelling through it scatter where they collide with the medium,
+/// which is found by delta (Woodcock) tracking: stepping by distances drawn for the densest part of the medium,
+/// then only accepting a collision in proportion to how dense the medium really is there.
+pub struct HeterogeneousMedium {
+    grid: VoxelGrid,
+    /// Scales the grid's values to densities
+    density: f64,
+    /// The largest density anywhere in the medium
+    majorant: f64,
+    /// Decides which way rays go when they scatter, and whether the medium glows
+    phase_function: Arc<dyn Material>,
+    bounding_box: Aabb,
+}
+
+impl HeterogeneousMedium {
+    /// The grid is stretched to fill the box between the corners `a` and `b`
+    pub fn new(grid: VoxelGrid, a: Pos, b: Pos, density: f64, phase_function: &Arc<dyn Material>) -> Self {
+        Self {
+            majorant: grid.max_value() * density,
+            grid,
+            density,
+            phase_function: Arc::clone(phase_function),
+            bounding_box: Aabb::from_points(a, b),
+        }
+    }
+
+    fn density_at(&self, pos: Pos) -> f64 {
+        let coordinate = |axis: usize| {
+            let span = self.bounding_box.axis(axis);
+            (pos[axis] - span.min) / span.size()
+        };
+        self.density * self.grid.sample(Vec3::new(coordinate(0), coordinate(1), coordinate(2)))
+    }
+}
+
+impl Hittable for HeterogeneousMedium {
+    fn hit(&self, ray: Ray, ray_t: Interval) -> Option<HitRecord> {
+        if self.majorant <= 0.0 {
+            return None;
+        }
+        // A ray starting inside the box already has its origin clipped to `ray_t`
+        let inside = self.bounding_box.hit_interval(ray, ray_t)?;
+        let ray_length = ray.direction.length();
+
+        let mut t = inside.min;
+        loop {
+            t -= (1.0 - rand_proportion()).ln() / (self.majorant * ray_length);
+            if t >= inside.max {
+                return None;
+            }
+            let pos = ray.at(t);
+            // Otherwise it was a null collision, with nothing there to scatter from
+            if rand_proportion() * self.majorant < self.density_at(pos) {
+                // There's no surface here, so the normal is arbitrary
+                return Some(HitRecord::new(t, pos, ray, Vec3::new(1.0, 0.0, 0.0), &self.phase_function));
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+}
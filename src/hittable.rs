@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use crate::aabb::Aabb;
 use crate::interval::Interval;
 use crate::material::Material;
 use crate::pos::Pos;
@@ -11,6 +12,11 @@ pub struct HitRecord {
     pub normal: Vec3,
     pub is_front_face: bool,
     pub material: Arc<dyn Material>,
+    /// Surface coordinates of the hit point
+    pub u: f64,
+    pub v: f64,
+    /// For hits on a triangle, the weight of each of its vertices at the hit point
+    pub barycentric: Option<[f64; 3]>,
 }
 
 impl HitRecord {
@@ -35,14 +41,91 @@ impl HitRecord {
             normal,
             is_front_face,
             material: Arc::clone(material),
+            u: 0.0,
+            v: 0.0,
+            barycentric: None,
         }
     }
+
+    pub fn with_uv(mut self, u: f64, v: f64) -> Self {
+        self.u = u;
+        self.v = v;
+        self
+    }
+
+    pub fn with_barycentric(mut self, barycentric: [f64; 3]) -> Self {
+        self.barycentric = Some(barycentric);
+        self
+    }
+
+    /// Shade using a normal that differs from the geometric one (such as one interpolated across a triangle).
+    /// Which side of the surface was hit is still decided by the geometric normal.
+    pub fn with_shading_normal(mut self, outward_normal: Vec3) -> Self {
+        self.normal = match self.is_front_face {
+            true => outward_normal,
+            false => -outward_normal,
+        };
+        self
+    }
 }
 
+/// A stretch of a ray's line that's inside a solid object, from where the line enters it to where it leaves.
+/// Either end is missing if the solid goes on forever that way, such as the half-space behind a plane.
+pub struct Span {
+    pub entry: Option<HitRecord>,
+    pub exit: Option<HitRecord>,
+}
+
+/// How far past a crossing to look for the next one
+const SPAN_STEP: f64 = 0.0001;
+
+/// Gives up on surfaces that keep being crossed, rather than walking them forever
+const MAX_SPAN_CROSSINGS: usize = 1024;
+
 pub trait Hittable : Send + Sync {
     fn hit(
         &self,
         ray: Ray,
         ray_t: Interval,
     ) -> Option<HitRecord>;
+
+    /// A box enclosing everything this object could report a hit for
+    fn bounding_box(&self) -> Aabb;
+
+    /// Every part of the ray's whole line, behind its origin too, that's inside this object, in order along the ray.
+    /// This only makes sense for closed surfaces, whose outward normals mark which side is inside.
+    ///
+    /// By default, this walks along the line from one crossing to the next,
+    /// entering at front faces and leaving at back faces.
+    fn hit_spans(&self, ray: Ray) -> Vec<Span> {
+        let mut spans = vec![];
+        let mut entry = None;
+        // How many surfaces we're inside, so overlapping pieces of one object make a single span
+        let mut depth = 0usize;
+        let mut t_min = f64::NEG_INFINITY;
+        for _ in 0..MAX_SPAN_CROSSINGS {
+            let Some(hit_record) = self.hit(ray, Interval::new(t_min, f64::INFINITY)) else {
+                break;
+            };
+            t_min = hit_record.t + SPAN_STEP;
+            if hit_record.is_front_face {
+                depth += 1;
+                if depth == 1 {
+                    entry = Some(hit_record);
+                }
+            }
+            else if depth <= 1 {
+                // With nothing to match it, the line must have been inside from the start
+                depth = 0;
+                spans.push(Span { entry: entry.take(), exit: Some(hit_record) });
+            }
+            else {
+                depth -= 1;
+            }
+        }
+        if depth > 0 {
+            spans.push(Span { entry, exit: None });
+        }
+        spans
+    }
 }
@@ -1,9 +1,11 @@
+use crate::aabb::Aabb;
 use crate::hittable::{HitRecord, Hittable};
 use crate::interval::Interval;
 use crate::ray::Ray;
 
 pub struct HittableList {
     objects: Vec<Box<dyn Hittable>>,
+    bounding_box: Aabb,
 }
 
 
@@ -14,6 +16,7 @@ impl HittableList {
     pub fn new() -> Self {
         Self {
             objects: vec![],
+            bounding_box: Aabb::empty(),
         }
     }
 
@@ -24,12 +27,19 @@ impl HittableList {
     }
 
     pub fn clear(&mut self) {
-        self.objects.clear()
+        self.objects.clear();
+        self.bounding_box = Aabb::empty();
     }
 
     pub fn add(&mut self, object: Box<dyn Hittable>) {
+        self.bounding_box = Aabb::enclosing(&self.bounding_box, &object.bounding_box());
         self.objects.push(object);
     }
+
+    /// Give up the list, handing back ownership of its objects
+    pub fn into_objects(self) -> Vec<Box<dyn Hittable>> {
+        self.objects
+    }
 }
 
 impl Hittable for HittableList {
@@ -46,4 +56,8 @@ impl Hittable for HittableList {
         }
         closest_hit_record
     }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
 }
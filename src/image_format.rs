@@ -0,0 +1,82 @@
+use std::io;
+use std::path::Path;
+use clap::ValueEnum;
+use png::BitDepth;
+use crate::framebuffer::Framebuffer;
+use crate::interval::Interval;
+use crate::exr::encode_exr;
+use crate::pfm::encode_pfm;
+use crate::png_encoder::encode_png;
+use crate::ppm::{encode_ppm_ascii, encode_ppm_binary};
+use crate::radiance_hdr::encode_radiance_hdr;
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub enum ImageFormat {
+    /// Plain-text (P3) PPM
+    #[value(alias = "p3")]
+    Ppm,
+    /// Binary (P6) PPM
+    #[value(alias = "p6")]
+    PpmBinary,
+    /// Binary (P6) PPM, with 16 bits per component
+    PpmBinary16,
+    /// 8-bit PNG
+    Png,
+    /// 16-bit PNG
+    Png16,
+    /// OpenEXR, linear and unclamped
+    Exr,
+    /// Radiance RGBE, linear and unclamped
+    Hdr,
+    /// Portable float map, linear and unclamped
+    Pfm,
+}
+
+impl ImageFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "ppm" => Some(ImageFormat::Ppm),
+            "png" => Some(ImageFormat::Png),
+            "exr" => Some(ImageFormat::Exr),
+            "hdr" => Some(ImageFormat::Hdr),
+            "pfm" => Some(ImageFormat::Pfm),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Ppm | ImageFormat::PpmBinary | ImageFormat::PpmBinary16 => "ppm",
+            ImageFormat::Png | ImageFormat::Png16 => "png",
+            ImageFormat::Exr => "exr",
+            ImageFormat::Hdr => "hdr",
+            ImageFormat::Pfm => "pfm",
+        }
+    }
+
+    pub fn encode(&self, framebuffer: &Framebuffer) -> io::Result<Vec<u8>> {
+        match self {
+            ImageFormat::Ppm => Ok(encode_ppm_ascii(framebuffer)),
+            ImageFormat::PpmBinary => Ok(encode_ppm_binary(framebuffer, 255)),
+            ImageFormat::PpmBinary16 => Ok(encode_ppm_binary(framebuffer, 65535)),
+            ImageFormat::Png => encode_png(framebuffer, BitDepth::Eight),
+            ImageFormat::Png16 => encode_png(framebuffer, BitDepth::Sixteen),
+            ImageFormat::Exr => Ok(encode_exr(framebuffer)),
+            ImageFormat::Hdr => Ok(encode_radiance_hdr(framebuffer)),
+            ImageFormat::Pfm => Ok(encode_pfm(framebuffer)),
+        }
+    }
+}
+
+fn linear_to_gamma(linear_component: f64) -> f64 {
+    linear_component.sqrt()
+}
+
+/// Gamma-correct a linear color component, and quantize it to an integer in `0..levels`
+pub fn quantize_component(linear_component: f64, levels: u32) -> u32 {
+    let levels = levels as f64;
+    // Keep the top of the range just below 1, so that full intensity lands in the last level
+    let intensity = Interval::new(0.000, (levels - 1.0) / levels);
+    (levels * intensity.clamp(linear_to_gamma(linear_component))).floor() as u32
+}
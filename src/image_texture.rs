@@ -0,0 +1,156 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::pos::Pos;
+use crate::png_decoder::decode_png;
+use crate::ppm::decode_ppm;
+use crate::texture::Texture;
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Decode {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl Display for ImageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageError::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            ImageError::Decode { path, message } => write!(f, "{}: {message}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+/// How coordinates outside the image are brought back into it
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AddressMode {
+    /// Repeat the image, tiling the surface
+    Wrap,
+    /// Extend the edge texels outwards
+    Clamp,
+    /// Repeat the image, flipping every other tile so neighbouring edges match
+    Mirror,
+}
+
+impl AddressMode {
+    /// Map a texel index along an axis of `size` texels into the image
+    fn apply(self, index: i64, size: usize) -> usize {
+        let size = size as i64;
+        let index = match self {
+            AddressMode::Wrap => index.rem_euclid(size),
+            AddressMode::Clamp => index.clamp(0, size - 1),
+            AddressMode::Mirror => {
+                let index = index.rem_euclid(2 * size);
+                if index < size { index } else { (2 * size) - 1 - index }
+            }
+        };
+        index as usize
+    }
+}
+
+/// How texels are combined when a lookup falls between them
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Filter {
+    /// Take the texel the lookup falls within, giving sharp, blocky magnification
+    Nearest,
+    /// Blend the four texels whose centers surround the lookup
+    Bilinear,
+}
+
+/// Maps an image onto a surface through its surface coordinates.
+/// (0, 0) is the bottom-left corner of the image, and (1, 1) the top-right.
+pub struct ImageTexture {
+    /// Linear color, already decoded from the file's sRGB encoding
+    image: Framebuffer,
+    address_mode: AddressMode,
+    filter: Filter,
+}
+
+impl ImageTexture {
+    /// `image` should hold linear colors
+    pub fn new(image: Framebuffer, address_mode: AddressMode, filter: Filter) -> Arc<Self> {
+        Arc::new(
+            Self {
+                image,
+                address_mode,
+                filter,
+            }
+        )
+    }
+
+    /// Load a PNG or PPM image, whose texels are taken to be sRGB-encoded
+    pub fn load(path: &Path, address_mode: AddressMode, filter: Filter) -> Result<Arc<Self>, ImageError> {
+        let bytes = fs::read(path).map_err(|source| ImageError::Io { path: path.to_path_buf(), source })?;
+        let decode_error = |message: String| ImageError::Decode { path: path.to_path_buf(), message };
+        // Pick the decoder by signature rather than extension, so a misnamed file still loads
+        let mut image = if bytes.starts_with(PNG_SIGNATURE) {
+            decode_png(&bytes)
+        }
+        else if bytes.starts_with(b"P3") || bytes.starts_with(b"P6") {
+            decode_ppm(&bytes)
+        }
+        else {
+            Err("not a PNG or PPM image".to_string())
+        }.map_err(decode_error)?;
+        if image.width() == 0 || image.height() == 0 {
+            return Err(decode_error("image is empty".to_string()));
+        }
+
+        for y in 0..image.height() {
+            for texel in image.row_mut(y) {
+                *texel = Color::new(srgb_to_linear(texel.r()), srgb_to_linear(texel.g()), srgb_to_linear(texel.b()));
+            }
+        }
+        Ok(Self::new(image, address_mode, filter))
+    }
+
+    fn texel(&self, x: i64, y: i64) -> Color {
+        let x = self.address_mode.apply(x, self.image.width());
+        let y = self.address_mode.apply(y, self.image.height());
+        self.image.row(y)[x]
+    }
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Undo the sRGB transfer function, so texels can be multiplied with other linear quantities
+fn srgb_to_linear(encoded: f64) -> f64 {
+    if encoded <= 0.04045 {
+        encoded / 12.92
+    }
+    else {
+        ((encoded + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _pos: Pos) -> Color {
+        // Continuous texel coordinates, with image rows running top to bottom
+        let x = u * self.image.width() as f64;
+        let y = (1.0 - v) * self.image.height() as f64;
+        match self.filter {
+            Filter::Nearest => self.texel(x.floor() as i64, y.floor() as i64),
+            Filter::Bilinear => {
+                // Texel centers sit at half-integer coordinates
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (tx, ty) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+                let top = ((1.0 - tx) * self.texel(x0, y0)) + (tx * self.texel(x0 + 1, y0));
+                let bottom = ((1.0 - tx) * self.texel(x0, y0 + 1)) + (tx * self.texel(x0 + 1, y0 + 1));
+                ((1.0 - ty) * top) + (ty * bottom)
+            }
+        }
+    }
+}
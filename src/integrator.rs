@@ -0,0 +1,84 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+use crate::color::Color;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::light::LightList;
+use crate::material::Material;
+use crate::ray::Ray;
+
+/// Everything an integrator can see of the scene being rendered
+pub struct SceneView<'a> {
+    pub world: &'a dyn Hittable,
+    /// Emitters that can be sampled directly. They're also somewhere in `world`.
+    pub lights: &'a LightList,
+    /// Colors rays that escape the world
+    pub background: &'a dyn Material,
+}
+
+impl SceneView<'_> {
+    /// The nearest hit along the ray, not counting the surface it leaves from
+    pub fn hit(&self, ray: Ray) -> Option<HitRecord> {
+        self.world.hit(ray, Interval::new(0.001, f64::MAX))
+    }
+}
+
+/// Works out how much light arrives back at the camera along a ray, which is how a scene's light transport is simulated
+pub trait Integrator : Send + Sync {
+    /// The light arriving back along `ray`, and how many rays were traced to find it
+    fn ray_color(&self, ray: Ray, scene: &SceneView) -> (Color, usize);
+}
+
+/// The integrators that can be picked for a render, on the command line or in a scene file
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegratorKind {
+    /// Full global illumination, following light as it bounces around the scene
+    Path,
+    /// Only light reaching the first surface seen straight from the lights or the background,
+    /// through any mirrors and glass on the way
+    Direct,
+    /// Surface normals as colors, for checking geometry
+    Debug,
+    /// How hemmed in by nearby geometry the first surface seen is, for quick previews of a scene's layout
+    AmbientOcclusion,
+}
+
+/// Light given off by the surface that `ray` hit.
+/// `bsdf_pdf` is the density with which the ray's direction was drawn from the BSDF at its origin,
+/// or `None` if it couldn't also have been found by sampling the lights (leaving the camera, or a delta bounce).
+pub fn emitted_light(scene: &SceneView, ray: Ray, hit_record: &HitRecord, bsdf_pdf: Option<f64>) -> Color {
+    let emitted_color = hit_record.material.emitted(hit_record);
+    match bsdf_pdf {
+        // Light sampling could have found this too, so it only gets its share
+        Some(bsdf_pdf) => power_heuristic(bsdf_pdf, scene.lights.pdf(ray.origin, ray.direction)) * emitted_color,
+        None => emitted_color,
+    }
+}
+
+/// Light arriving at the hit point straight from a light, found by casting a shadow ray towards one
+pub fn sample_lights(scene: &SceneView, ray: Ray, hit_record: &HitRecord) -> Color {
+    let Some(direction) = scene.lights.sample_direction(hit_record.pos) else {
+        return Color::black();
+    };
+    let light_pdf = scene.lights.pdf(hit_record.pos, direction);
+    let bsdf = hit_record.material.eval(ray, hit_record, direction);
+    if light_pdf <= 0.0 || bsdf == Color::black() {
+        return Color::black();
+    }
+
+    // Whatever the shadow ray hits first is what's seen in that direction, whether the light or something blocking it
+    let Some(light_hit) = scene.hit(Ray::with_time(hit_record.pos, direction, ray.time)) else {
+        return Color::black();
+    };
+    let emitted_color = light_hit.material.emitted(&light_hit);
+    let weight = power_heuristic(light_pdf, hit_record.material.pdf(ray, hit_record, direction));
+    (weight / light_pdf) * (bsdf * emitted_color)
+}
+
+/// Multiple importance sampling weight for a sample drawn by a strategy with density `pdf`,
+/// when another strategy could have drawn it with density `other_pdf`
+fn power_heuristic(pdf: f64, other_pdf: f64) -> f64 {
+    let (a, b) = (pdf * pdf, other_pdf * other_pdf);
+    if a + b <= 0.0 { 0.0 } else { a / (a + b) }
+}
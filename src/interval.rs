@@ -25,6 +25,24 @@ impl Interval {
         )
     }
 
+    /// The smallest interval containing both `a` and `b`
+    pub fn enclosing(a: Interval, b: Interval) -> Self {
+        Self::new(
+            f64::min(a.min, b.min),
+            f64::max(a.max, b.max),
+        )
+    }
+
+    pub fn size(&self) -> f64 {
+        self.max - self.min
+    }
+
+    /// Grow the interval by `delta` in total, split evenly between each end
+    pub fn expand(&self, delta: f64) -> Self {
+        let padding = delta / 2.0;
+        Self::new(self.min - padding, self.max + padding)
+    }
+
     /// Inclusive contains
     pub fn contains(&self, val: f64) -> bool {
         self.min <= val && val <= self.max
@@ -0,0 +1,42 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+use crate::color::Color;
+use crate::hittable::HitRecord;
+use crate::material::{Material, ScatterRecord};
+use crate::ray::Ray;
+use crate::texture::Texture;
+use crate::vec3::Vec3;
+
+/// Probability density of each direction, when every direction is equally likely
+const UNIFORM_SPHERE_PDF: f64 = 1.0 / (4.0 * PI);
+
+/// Phase function for participating media, scattering equally in every direction
+pub struct IsotropicMaterial {
+    albedo: Arc<dyn Texture>,
+}
+
+impl IsotropicMaterial {
+    pub fn with_texture(albedo: Arc<dyn Texture>) -> Arc<Self> {
+        Arc::new(
+            Self {
+                albedo,
+            }
+        )
+    }
+}
+
+impl Material for IsotropicMaterial {
+    fn scatter(&self, ray: Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let scattered = Ray::with_time(hit_record.pos, Vec3::random_unit_vector(), ray.time);
+        let value = UNIFORM_SPHERE_PDF * self.albedo.value(hit_record.u, hit_record.v, hit_record.pos);
+        Some(ScatterRecord::new(scattered, value, UNIFORM_SPHERE_PDF))
+    }
+
+    fn eval(&self, _ray: Ray, hit_record: &HitRecord, _direction: Vec3) -> Color {
+        UNIFORM_SPHERE_PDF * self.albedo.value(hit_record.u, hit_record.v, hit_record.pos)
+    }
+
+    fn pdf(&self, _ray: Ray, _hit_record: &HitRecord, _direction: Vec3) -> f64 {
+        UNIFORM_SPHERE_PDF
+    }
+}
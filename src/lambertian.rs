@@ -1,16 +1,23 @@
+use std::f64::consts::PI;
 use std::sync::Arc;
 use crate::color::Color;
 use crate::hittable::HitRecord;
-use crate::material::Material;
+use crate::material::{Material, ScatterRecord};
+use crate::onb::Onb;
 use crate::ray::Ray;
+use crate::texture::{SolidColor, Texture};
 use crate::vec3::Vec3;
 
 pub struct LambertianMaterial {
-    albedo: Color,
+    albedo: Arc<dyn Texture>,
 }
 
 impl LambertianMaterial {
     pub fn new(albedo: Color) -> Arc<Self> {
+        Self::with_texture(SolidColor::new(albedo))
+    }
+
+    pub fn with_texture(albedo: Arc<dyn Texture>) -> Arc<Self> {
         Arc::new(
             Self {
                 albedo,
@@ -20,16 +27,26 @@ impl LambertianMaterial {
 }
 
 impl Material for LambertianMaterial {
-    fn scatter(&self, ray: Ray, hit_record: Option<&HitRecord>) -> Option<(Ray, Color)> {
-        let hit_record = hit_record.expect("Expected a hit record to be available");
-        let mut scatter_direction = hit_record.normal + Vec3::random_unit_vector();
+    fn scatter(&self, ray: Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        // Favour directions near the normal, in proportion to how much light they contribute
+        let local = Vec3::random_cosine_direction();
+        let scatter_direction = Onb::new(hit_record.normal).local(local.x, local.y, local.z);
+        let scattered = Ray::with_time(hit_record.pos, scatter_direction, ray.time);
+        let cos_theta = local.z;
+        let value = (cos_theta / PI) * self.albedo.value(hit_record.u, hit_record.v, hit_record.pos);
+        Some(ScatterRecord::new(scattered, value, cos_theta / PI))
+    }
 
-        // Catch degenerate scatter direction
-        if scatter_direction.is_near_zero() {
-            scatter_direction = hit_record.normal;
+    fn eval(&self, _ray: Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let cos_theta = Vec3::dot(hit_record.normal, direction.unit_vector());
+        if cos_theta <= 0.0 {
+            return Color::black();
         }
+        (cos_theta / PI) * self.albedo.value(hit_record.u, hit_record.v, hit_record.pos)
+    }
 
-        let scattered = Ray::new(hit_record.pos, scatter_direction);
-        Some((scattered, self.albedo))
+    fn pdf(&self, _ray: Ray, hit_record: &HitRecord, direction: Vec3) -> f64 {
+        let cos_theta = Vec3::dot(hit_record.normal, direction.unit_vector());
+        f64::max(cos_theta, 0.0) / PI
     }
 }
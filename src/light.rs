@@ -0,0 +1,152 @@
+use crate::pos::Pos;
+use crate::utils::rand_proportion;
+use crate::vec3::Vec3;
+
+/// An emitter that rays can be aimed at directly, rather than only finding it by chance
+pub trait Light : Send + Sync {
+    /// A random unit direction from `origin` towards a point on the light,
+    /// or `None` if the light can't be seen from there at all.
+    /// It must be a unit vector, as shadow rays skip a fixed length of it to avoid hitting the surface they leave from.
+    fn sample_direction(&self, origin: Pos) -> Option<Vec3>;
+
+    /// Probability density over solid angle that `sample_direction` picks `direction` from `origin`
+    fn pdf(&self, origin: Pos, direction: Vec3) -> f64;
+}
+
+/// The lights in a scene, sampled as one: a light is picked at random, then a direction towards it
+pub struct LightList {
+    lights: Vec<Box<dyn Light>>,
+}
+
+impl LightList {
+    pub fn new() -> Self {
+        Self {
+            lights: vec![],
+        }
+    }
+
+    pub fn add(&mut self, light: Box<dyn Light>) {
+        self.lights.push(light);
+    }
+
+    pub fn sample_direction(&self, origin: Pos) -> Option<Vec3> {
+        if self.lights.is_empty() {
+            return None;
+        }
+        let index = ((rand_proportion() * self.lights.len() as f64) as usize).min(self.lights.len() - 1);
+        self.lights[index].sample_direction(origin)
+    }
+
+    /// Any of the lights could have picked the direction, so this is the average of their densities
+    pub fn pdf(&self, origin: Pos, direction: Vec3) -> f64 {
+        if self.lights.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = self.lights.iter().map(|light| light.pdf(origin, direction)).sum();
+        total / self.lights.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+    use std::sync::Arc;
+    use super::*;
+    use crate::color::Color;
+    use crate::diffuse_light::DiffuseLightMaterial;
+    use crate::material::Material;
+    use crate::quad::Quad;
+    use crate::sphere::Sphere;
+    use crate::utils::seed_rng;
+
+    const SAMPLE_COUNT: usize = 200_000;
+
+    fn lamp() -> Arc<dyn Material> {
+        DiffuseLightMaterial::new(Color::white())
+    }
+
+    fn sphere_light() -> Sphere {
+        Sphere::new(Pos::new(0.0, 3.0, 0.0), 1.0, &lamp())
+    }
+
+    fn quad_light() -> Quad {
+        Quad::new(Pos::new(-1.0, 1.0, -1.0), Vec3::new(2.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 2.0), &lamp())
+    }
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64, what: &str) {
+        assert!((actual - expected).abs() <= tolerance * expected, "{what}: {actual}, expected {expected}");
+    }
+
+    /// The density must integrate to one over all directions, estimated here from uniformly drawn directions
+    fn assert_pdf_integrates_to_one(light: &dyn Light, origin: Pos) {
+        let total: f64 = (0..SAMPLE_COUNT).map(|_| light.pdf(origin, Vec3::random_unit_vector())).sum();
+        assert_close(4.0 * PI * total / SAMPLE_COUNT as f64, 1.0, 0.03, "integral of the pdf");
+    }
+
+    /// Sampled directions must be unit length and have the density `pdf` reports for them,
+    /// so that weighting each by 1/pdf measures the solid angle the light fills
+    fn assert_samples_match_pdf(light: &dyn Light, origin: Pos, solid_angle: f64) {
+        let mut total = 0.0;
+        for _ in 0..SAMPLE_COUNT {
+            let direction = light.sample_direction(origin).expect("light should be visible");
+            assert_close(direction.length(), 1.0, 1e-9, "length of sampled direction");
+            let pdf = light.pdf(origin, direction);
+            assert!(pdf > 0.0, "sampled direction {direction:?} has no density");
+            total += 1.0 / pdf;
+        }
+        assert_close(total / SAMPLE_COUNT as f64, solid_angle, 0.03, "solid angle");
+    }
+
+    #[test]
+    fn sphere_sampling_matches_its_pdf() {
+        seed_rng(1);
+        let sphere = sphere_light();
+        let origin = Pos::zero();
+        // A sphere of radius 1 seen from 3 away fills a cone with cos(theta) = sqrt(8) / 3
+        let solid_angle = 2.0 * PI * (1.0 - (8f64.sqrt() / 3.0));
+        assert_pdf_integrates_to_one(&sphere, origin);
+        assert_samples_match_pdf(&sphere, origin, solid_angle);
+        assert!(sphere.sample_direction(Pos::new(0.0, 3.0, 0.5)).is_none(), "a sphere can't be sampled from inside");
+    }
+
+    #[test]
+    fn quad_sampling_matches_its_pdf() {
+        seed_rng(2);
+        let quad = quad_light();
+        let origin = Pos::zero();
+        // Each quarter of the square is a 1x1 rectangle with a corner above the origin
+        let solid_angle = 4.0 * (1.0 / 3f64.sqrt()).atan();
+        assert_pdf_integrates_to_one(&quad, origin);
+        assert_samples_match_pdf(&quad, origin, solid_angle);
+    }
+
+    #[test]
+    fn distant_quad_sampling_matches_its_pdf() {
+        seed_rng(3);
+        // Far enough that its solid angle is tiny, and unnormalized directions would be very long
+        let quad = Quad::new(Pos::new(-100.0, 2000.0, -100.0), Vec3::new(200.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 200.0), &lamp());
+        let solid_angle = 4.0 * ((100.0 * 100.0) / (2000.0 * (2.0 * 100.0 * 100.0 + 2000.0 * 2000.0f64).sqrt())).atan();
+        assert_samples_match_pdf(&quad, Pos::zero(), solid_angle);
+    }
+
+    #[test]
+    fn list_pdf_averages_its_lights() {
+        seed_rng(4);
+        let mut lights = LightList::new();
+        assert!(lights.sample_direction(Pos::zero()).is_none());
+        assert_eq!(lights.pdf(Pos::zero(), Vec3::new(0.0, 1.0, 0.0)), 0.0);
+
+        lights.add(Box::new(sphere_light()));
+        lights.add(Box::new(Quad::new(Pos::new(-1.0, -1.0, -1.0), Vec3::new(0.0, 0.0, 2.0), Vec3::new(2.0, 0.0, 0.0), &lamp())));
+        let total: f64 = (0..SAMPLE_COUNT).map(|_| lights.pdf(Pos::zero(), Vec3::random_unit_vector())).sum();
+        assert_close(4.0 * PI * total / SAMPLE_COUNT as f64, 1.0, 0.03, "integral of the pdf");
+        let solid_angle = (2.0 * PI * (1.0 - (8f64.sqrt() / 3.0))) + (4.0 * (1.0 / 3f64.sqrt()).atan());
+        // The lights don't overlap, so each direction is only ever picked through one of them
+        let mut total = 0.0;
+        for _ in 0..SAMPLE_COUNT {
+            let direction = lights.sample_direction(Pos::zero()).unwrap();
+            total += 1.0 / lights.pdf(Pos::zero(), direction);
+        }
+        assert_close(total / SAMPLE_COUNT as f64, solid_angle, 0.03, "solid angle");
+    }
+}
@@ -4,76 +4,98 @@ mod color;
 mod ray;
 mod hittable;
 mod sphere;
+mod onb;
+mod plane;
+mod quad;
+mod disk;
+mod box_primitive;
+mod cylinder;
+mod csg;
+mod constant_medium;
+mod heterogeneous_medium;
+mod voxel_grid;
+mod moving_sphere;
 mod hittable_list;
 mod utils;
 mod interval;
 mod camera;
+mod integrator;
+mod path_integrator;
+mod direct_lighting;
+mod debug_integrator;
+mod ambient_occlusion;
 mod material;
+mod light;
 mod lambertian;
 mod dielectric;
 mod metal;
 mod gradient;
+mod aabb;
+mod bvh;
+mod diffuse_light;
+mod isotropic;
+mod henyey_greenstein;
+mod solid_color;
+mod scene;
+mod scene_file;
+mod cli;
+mod framebuffer;
+mod image_format;
+mod ppm;
+mod png_encoder;
+mod exr;
+mod radiance_hdr;
+mod pfm;
+mod triangle;
+mod triangle_mesh;
+mod matrix4;
+mod transform;
+mod obj;
+mod sink;
+mod texture;
+mod image_texture;
+mod png_decoder;
+mod perlin;
+mod noise_texture;
 
+use std::error::Error;
 use std::f64::consts::PI;
-use std::fs;
-use std::fs::File;
-use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::sync::Arc;
-use std::time::SystemTime;
+use clap::Parser;
 use rand::prelude::IndexedRandom;
+use crate::bvh::BvhNode;
 use crate::camera::Camera;
+use crate::cli::{BuiltinScene, Cli};
 use crate::color::Color;
 use crate::dielectric::DielectricMaterial;
+use crate::diffuse_light::DiffuseLightMaterial;
 use crate::gradient::GradientMaterial;
 use crate::hittable::Hittable;
 use crate::hittable_list::HittableList;
+use crate::image_format::ImageFormat;
+use crate::integrator::IntegratorKind;
 use crate::lambertian::LambertianMaterial;
+use crate::light::LightList;
 use crate::material::Material;
 use crate::metal::MetalMaterial;
+use crate::plane::Plane;
 use crate::pos::Pos;
+use crate::solid_color::SolidColorMaterial;
 use crate::ray::Ray;
+use crate::scene::{camera_with_params, CameraSettings, SceneParameters};
+use crate::scene_file::SceneFile;
+use crate::sink::FileSink;
 use crate::sphere::Sphere;
-use crate::utils::{degrees_to_radians, rand_double, rand_proportion};
+use crate::utils::{degrees_to_radians, rand_double, rand_proportion, seed_rng, with_rng};
 use crate::vec3::Vec3;
 
-#[derive(Copy, Clone)]
-struct SceneParameters {
-    aspect_ratio: f64,
-    image_width: usize,
-    samples_per_pixel: usize,
-    max_ray_bounces: usize,
-}
-
-fn camera_with_params(
-    scene_params: SceneParameters,
-    background_material: Arc<GradientMaterial>,
-    look_from: Pos,
-    look_at: Pos,
-    up: Vec3,
-    vertical_field_of_view_angle: f64,
-    defocus_angle: f64,
-    focus_distance: f64,
-) -> Camera {
-    Camera::new(
-        scene_params.aspect_ratio,
-        scene_params.image_width,
-        background_material,
-        vertical_field_of_view_angle,
-        look_from,
-        look_at,
-        up,
-        defocus_angle,
-        focus_distance,
-        scene_params.samples_per_pixel,
-        scene_params.max_ray_bounces,
-    )
-}
-
 fn main_cover(scene_parameters: SceneParameters) -> (HittableList, Camera) {
     let mut world = HittableList::new();
 
     let material_ground = LambertianMaterial::new(Color::new(0.5, 0.5, 0.5));
-    world.add(Box::new(Sphere::new(Pos::new(0.0, -1000.0, 0.0), 1000.0, &(material_ground as Arc<dyn Material>))));
+    world.add(Box::new(Plane::new(Pos::zero(), Vec3::new(0.0, 1.0, 0.0), &(material_ground as Arc<dyn Material>))));
 
     for a in -11..11 {
         for b in -11..11 {
@@ -83,7 +105,7 @@ fn main_cover(scene_parameters: SceneParameters) -> (HittableList, Camera) {
                 b as f64 + (0.9 * rand_proportion()),
             );
             if (center - *Pos::new(4.0, 0.2, 0.0)).length() > 0.9 {
-                let mat_selection = [0, 1, 2].choose(&mut rand::thread_rng()).expect("Failed to make a selection");
+                let mat_selection = with_rng(|rng| *[0, 1, 2].choose(rng).expect("Failed to make a selection"));
                 let material: Arc<dyn Material> = match mat_selection {
                     0 => {
                         // Diffuse
@@ -124,12 +146,14 @@ fn main_cover(scene_parameters: SceneParameters) -> (HittableList, Camera) {
                 Color::new(0.5, 0.7, 1.0),
                 Color::white(),
             ),
-            Pos::new(13.0, 2.0, 3.0),
-            Pos::new(0.0, 0.0, 0.0),
-            Vec3::new(0.0, 1.0, 0.0),
-            20.0,
-            0.6,
-            10.0,
+            CameraSettings {
+                look_from: Pos::new(13.0, 2.0, 3.0),
+                look_at: Pos::new(0.0, 0.0, 0.0),
+                up: Vec3::new(0.0, 1.0, 0.0),
+                vertical_field_of_view_angle: 20.0,
+                defocus_angle: 0.6,
+                focus_distance: 10.0,
+            },
         ),
     )
 }
@@ -142,7 +166,7 @@ fn three_balls(scene_params: SceneParameters) -> (HittableList, Camera) {
     let material_left = DielectricMaterial::new(1.5);
     let material_right = MetalMaterial::new(Color::new(0.8, 0.6, 0.2), 0.0);
 
-    world.add(Box::new(Sphere::new(Pos::new(0.0, -100.5, -1.0), 100.0, &(material_ground as Arc<dyn Material>))));
+    world.add(Box::new(Plane::new(Pos::new(0.0, -0.5, 0.0), Vec3::new(0.0, 1.0, 0.0), &(material_ground as Arc<dyn Material>))));
     world.add(Box::new(Sphere::new(Pos::new(0.0, 0.0, -1.0), 0.5, &(material_center as Arc<dyn Material>))));
     world.add(Box::new(Sphere::new(Pos::new(-1.0, 0.0, -1.0), 0.5, &(Arc::clone(&material_left) as Arc<dyn Material>))));
     world.add(Box::new(Sphere::new(Pos::new(-1.0, 0.0, -1.0), -0.4, &(material_left as Arc<dyn Material>))));
@@ -156,16 +180,57 @@ fn three_balls(scene_params: SceneParameters) -> (HittableList, Camera) {
                 Color::new(0.5, 0.7, 1.0),
                 Color::white(),
             ),
-            Pos::new(-2.0, 2.0, 1.0),
-            Pos::new(0.0, 0.0, -1.0),
-            Vec3::new(0.0, 1.0, 0.0),
-            20.0,
-            10.0,
-            3.4,
+            CameraSettings {
+                look_from: Pos::new(-2.0, 2.0, 1.0),
+                look_at: Pos::new(0.0, 0.0, -1.0),
+                up: Vec3::new(0.0, 1.0, 0.0),
+                vertical_field_of_view_angle: 20.0,
+                defocus_angle: 10.0,
+                focus_distance: 3.4,
+            },
         ),
     )
 }
 
+fn lamps(scene_params: SceneParameters) -> (HittableList, Camera) {
+    let mut world = HittableList::new();
+
+    let material_ground = LambertianMaterial::new(Color::new(0.5, 0.5, 0.5));
+    let material_center = LambertianMaterial::new(Color::new(0.1, 0.2, 0.5));
+    let material_left = DielectricMaterial::new(1.5);
+    let material_right = MetalMaterial::new(Color::new(0.8, 0.6, 0.2), 0.1);
+    let material_warm_lamp = DiffuseLightMaterial::new(Color::new(6.0, 4.5, 3.0));
+    let material_cool_lamp = DiffuseLightMaterial::new(Color::new(1.5, 2.5, 5.0));
+
+    world.add(Box::new(Plane::new(Pos::new(0.0, -0.5, 0.0), Vec3::new(0.0, 1.0, 0.0), &(material_ground as Arc<dyn Material>))));
+    world.add(Box::new(Sphere::new(Pos::new(0.0, 0.0, -1.0), 0.5, &(material_center as Arc<dyn Material>))));
+    world.add(Box::new(Sphere::new(Pos::new(-1.0, 0.0, -1.0), 0.5, &(material_left as Arc<dyn Material>))));
+    world.add(Box::new(Sphere::new(Pos::new(1.0, 0.0, -1.0), 0.5, &(material_right as Arc<dyn Material>))));
+    // Lamps hanging above the balls, providing the only light in the scene
+    let warm_lamp = Sphere::new(Pos::new(-0.4, 1.4, -1.8), 0.3, &(material_warm_lamp as Arc<dyn Material>));
+    let cool_lamp = Sphere::new(Pos::new(0.8, 0.9, -1.8), 0.2, &(material_cool_lamp as Arc<dyn Material>));
+    let mut lights = LightList::new();
+    lights.add(Box::new(warm_lamp.clone()));
+    lights.add(Box::new(cool_lamp.clone()));
+    world.add(Box::new(warm_lamp));
+    world.add(Box::new(cool_lamp));
+
+    let mut camera = camera_with_params(
+        scene_params,
+        SolidColorMaterial::new(Color::black()),
+        CameraSettings {
+            look_from: Pos::new(-2.0, 2.0, 1.0),
+            look_at: Pos::new(0.0, 0.0, -1.0),
+            up: Vec3::new(0.0, 1.0, 0.0),
+            vertical_field_of_view_angle: 30.0,
+            defocus_angle: 0.0,
+            focus_distance: 3.4,
+        },
+    );
+    camera.set_lights(lights);
+    (world, camera)
+}
+
 struct Hsv {
     // 0 - 360
     hue: f64,
@@ -331,70 +396,166 @@ fn pyramid(
                 Color::white(),
                 Color::rgb(100, 150, 130),
             ),
-            look_from,
-            look_to,
-            Vec3::new(0.0, 1.0, 0.0),
-            20.0,
-            0.0,
-            3.4,
+            CameraSettings {
+                look_from,
+                look_at: look_to,
+                up: Vec3::new(0.0, 1.0, 0.0),
+                vertical_field_of_view_angle: 20.0,
+                defocus_angle: 0.0,
+                focus_distance: 3.4,
+            },
         ),
     )
 }
 
-fn main3() -> std::io::Result<()> {
-    let scene = 2;
-    let scene_params = SceneParameters {
-        aspect_ratio: 16.0 / 9.0,
-        image_width: 400,
-        samples_per_pixel: 10,
-        max_ray_bounces: 50,
-    };
-    let (world, camera) = match scene {
-        0 => three_balls(scene_params),
-        1 => main_cover(scene_params),
-        2 => pyramid(
+fn build_builtin_scene(scene: BuiltinScene, scene_params: SceneParameters) -> (HittableList, Camera) {
+    match scene {
+        BuiltinScene::ThreeBalls => three_balls(scene_params),
+        BuiltinScene::Cover => main_cover(scene_params),
+        BuiltinScene::Pyramid => pyramid(
             scene_params,
             Pos::new(0.0, 4.0, 12.0),
             Pos::new(0.0, 0.0, 0.0),
         ),
-        val => panic!("Unknown scene {val}"),
-    };
-    camera.render(&world).map(|_| ())
+        BuiltinScene::Lamps => lamps(scene_params),
+    }
 }
 
-fn main() -> std::io::Result<()> {
-    let look_from = Pos::new(0.0, 4.0, -6.0);
-    let look_to = Pos::new(0.0, 0.5, 0.0);
-    let scene_params = SceneParameters {
-        aspect_ratio: 16.0 / 9.0,
-        image_width: 400,
-        samples_per_pixel: 100,
-        max_ray_bounces: 50,
+/// Path of a numbered turntable frame, next to the requested output path
+fn frame_output_path(output: &Path, frame: usize) -> PathBuf {
+    let stem = output.file_stem().and_then(|s| s.to_str()).unwrap_or("frame");
+    let file_name = match output.extension().and_then(|s| s.to_str()) {
+        Some(extension) => format!("{stem}_{frame:04}.{extension}"),
+        None => format!("{stem}_{frame:04}"),
     };
+    output.with_file_name(file_name)
+}
 
-    let radius = look_from.z;
-    let step_count = 300;
-    let start_time = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).expect("Failed to retrieve time since epoch");
-    let output_dir_path = format!("./movie_{}", start_time.as_secs());
-    fs::create_dir(output_dir_path.clone())?;
+/// Where the rendered image(s) should go, according to the command line
+fn sink_for_path(cli: &Cli, path: &Path, format: ImageFormat) -> FileSink {
+    let sink = FileSink::new(path, format);
+    match &cli.history_dir {
+        Some(history_dir) => sink.with_history_dir(history_dir),
+        None => sink,
+    }
+}
 
+/// Every requested output path, along with the format to write it in
+fn outputs(cli: &Cli) -> Result<Vec<(PathBuf, ImageFormat)>, Box<dyn Error>> {
+    if cli.format.is_some() && cli.outputs.len() > 1 {
+        return Err("--format can only be used with a single --output, otherwise formats are taken from each file extension".into());
+    }
+    cli.outputs.iter().map(|path| {
+        let format = match cli.format {
+            Some(format) => format,
+            None => ImageFormat::from_path(path).ok_or_else(|| {
+                format!("can't tell the output format from `{}`, pass --format", path.display())
+            })?,
+        };
+        Ok((path.clone(), format))
+    }).collect()
+}
+
+fn render_turntable(cli: &Cli, scene_params: SceneParameters, outputs: &[(PathBuf, ImageFormat)], step_count: usize) -> Result<(), Box<dyn Error>> {
+    if !matches!(cli.scene, BuiltinScene::Pyramid) || cli.scene_file.is_some() {
+        return Err("--turntable is only supported for the pyramid scene".into());
+    }
+
+    let look_to = Pos::new(0.0, 0.5, 0.0);
+    let camera_height = 4.0;
     let camera_distance = 12.0;
     for rotation_step in 0..step_count {
         println!("Process rotation step #{rotation_step} / {step_count}");
         let angle = (rotation_step as f64 * 2.0 * PI) / step_count as f64;
         let look_from_x = camera_distance * angle.sin();
         let look_from_z = camera_distance * angle.cos();
-        let look_from = Pos::new(look_from_x, look_from.y, look_from_z);
-        //let look_to = look_to + Vec3::new(0.0, 0.0, 0.0);
-        let (world, camera) = pyramid(
+        let look_from = Pos::new(look_from_x, camera_height, look_from_z);
+        if let Some(seed) = cli.seed {
+            seed_rng(seed);
+        }
+        let (world, mut camera) = pyramid(
             scene_params,
             look_from,
             look_to,
         );
-        let ppm = camera.render(&world).expect("Failed to render");
-        let mut frame_file = File::create(format!("{output_dir_path}/{}.ppm", rotation_step))?;
-        frame_file.write(&ppm)?;
+        if let Some(seed) = cli.seed {
+            camera.set_seed(seed);
+        }
+        let framebuffer = camera.render(&BvhNode::new(world));
+        for (path, format) in outputs.iter() {
+            sink_for_path(cli, &frame_output_path(path, rotation_step), *format).write(&framebuffer)?;
+        }
+    }
+    Ok(())
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let outputs = outputs(&cli)?;
+
+    if let Some(threads) = cli.threads {
+        rayon::ThreadPoolBuilder::new().num_threads(threads).build_global()?;
+    }
+
+    // Random choices made while building the scene should be reproducible too
+    if let Some(seed) = cli.seed {
+        seed_rng(seed);
+    }
+
+    let defaults = SceneParameters {
+        aspect_ratio: 16.0 / 9.0,
+        image_width: 400,
+        samples_per_pixel: 100,
+        max_ray_bounces: 50,
+        russian_roulette_depth: 5,
+        integrator: IntegratorKind::Path,
+        ambient_occlusion_samples: 16,
+        ambient_occlusion_distance: 1.0,
+    };
+    let scene_file = cli.scene_file.as_deref().map(SceneFile::open).transpose()?;
+    let scene_params = scene_file.as_ref().map_or(defaults, |scene_file| scene_file.parameters(defaults));
+    let scene_params = SceneParameters {
+        aspect_ratio: cli.aspect_ratio.unwrap_or(scene_params.aspect_ratio),
+        image_width: cli.width.unwrap_or(scene_params.image_width),
+        samples_per_pixel: cli.samples.unwrap_or(scene_params.samples_per_pixel),
+        max_ray_bounces: cli.max_bounces.unwrap_or(scene_params.max_ray_bounces),
+        russian_roulette_depth: cli.russian_roulette_depth.unwrap_or(scene_params.russian_roulette_depth),
+        integrator: cli.integrator.unwrap_or(scene_params.integrator),
+        ambient_occlusion_samples: cli.ao_samples.unwrap_or(scene_params.ambient_occlusion_samples),
+        ambient_occlusion_distance: cli.ao_distance.unwrap_or(scene_params.ambient_occlusion_distance),
+    };
+    if scene_params.image_width == 0 || scene_params.samples_per_pixel == 0 {
+        return Err("the image width and samples per pixel must be at least 1".into());
+    }
+    if scene_params.ambient_occlusion_samples == 0
+        || scene_params.ambient_occlusion_distance.is_nan() || scene_params.ambient_occlusion_distance <= 0.0 {
+        return Err("the ambient occlusion samples and distance must be positive".into());
     }
 
+    if let Some(step_count) = cli.turntable {
+        return render_turntable(&cli, scene_params, &outputs, step_count);
+    }
+
+    let (world, mut camera) = match &scene_file {
+        Some(scene_file) => scene_file.build(scene_params)?,
+        None => build_builtin_scene(cli.scene, scene_params),
+    };
+    if let Some(seed) = cli.seed {
+        camera.set_seed(seed);
+    }
+    let framebuffer = camera.render(&BvhNode::new(world));
+    // Every output is written from the same render, so HDR and LDR copies match
+    for (path, format) in outputs.iter() {
+        sink_for_path(&cli, path, *format).write(&framebuffer)?;
+    }
     Ok(())
 }
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,11 +1,70 @@
 use crate::color::Color;
 use crate::hittable::HitRecord;
 use crate::ray::Ray;
+use crate::vec3::Vec3;
 
-pub trait Material {
-    fn scatter(
-        &self,
-        ray: Ray,
-        hit_record: Option<&HitRecord>,
-    ) -> Option<(Ray, Color)>;
+/// A direction drawn from a material's scattering distribution
+pub struct ScatterRecord {
+    pub ray: Ray,
+    /// The BSDF for the scattered direction, times the cosine of its angle with the normal.
+    /// For delta scattering, this is the whole weight of the scattered ray.
+    pub value: Color,
+    /// Probability density of drawing the scattered direction, over solid angle.
+    /// Delta scattering has no density to speak of, so uses 1.
+    pub pdf: f64,
+    /// Drawn from a distribution with all its weight in a single direction, such as a mirror's reflection,
+    /// so no other way of picking directions (such as aiming at lights) could ever find it
+    pub is_delta: bool,
+}
+
+impl ScatterRecord {
+    pub fn new(ray: Ray, value: Color, pdf: f64) -> Self {
+        Self {
+            ray,
+            value,
+            pdf,
+            is_delta: false,
+        }
+    }
+
+    pub fn delta(ray: Ray, weight: Color) -> Self {
+        Self {
+            ray,
+            value: weight,
+            pdf: 1.0,
+            is_delta: true,
+        }
+    }
+
+    /// How much of the light arriving along the scattered ray carries on along the incoming one
+    pub fn weight(&self) -> Color {
+        (1.0 / self.pdf) * self.value
+    }
+}
+
+pub trait Material : Send + Sync {
+    /// Draw a direction for the ray to carry on in, or `None` if the ray is absorbed
+    fn scatter(&self, ray: Ray, hit_record: &HitRecord) -> Option<ScatterRecord>;
+
+    /// The BSDF times the cosine term, for light arriving from `direction` and leaving back along `ray`.
+    /// Delta materials give nothing, since no particular direction is ever exactly the one they pick.
+    fn eval(&self, _ray: Ray, _hit_record: &HitRecord, _direction: Vec3) -> Color {
+        Color::black()
+    }
+
+    /// Probability density that `scatter` picks `direction`, over solid angle
+    fn pdf(&self, _ray: Ray, _hit_record: &HitRecord, _direction: Vec3) -> f64 {
+        0.0
+    }
+
+    /// Light given off by the surface at the hit point.
+    /// Most materials don't emit anything.
+    fn emitted(&self, _hit_record: &HitRecord) -> Color {
+        Color::black()
+    }
+
+    /// Light arriving along a ray that escapes the scene, for materials used as the background
+    fn background(&self, _ray: Ray) -> Color {
+        Color::black()
+    }
 }
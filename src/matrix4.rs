@@ -0,0 +1,194 @@
+use std::ops::Mul;
+use crate::pos::Pos;
+use crate::utils::degrees_to_radians;
+use crate::vec3::Vec3;
+
+/// A 4x4 matrix acting on column vectors, for affine transformations
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Matrix4 {
+    /// Row-major
+    m: [[f64; 4]; 4],
+}
+
+impl Matrix4 {
+    pub fn new(m: [[f64; 4]; 4]) -> Self {
+        Self { m }
+    }
+
+    pub fn identity() -> Self {
+        Self::scale(Vec3::new(1.0, 1.0, 1.0))
+    }
+
+    pub fn translation(offset: Vec3) -> Self {
+        Self::new([
+            [1.0, 0.0, 0.0, offset.x],
+            [0.0, 1.0, 0.0, offset.y],
+            [0.0, 0.0, 1.0, offset.z],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Scale by a possibly different factor along each axis
+    pub fn scale(factors: Vec3) -> Self {
+        Self::new([
+            [factors.x, 0.0, 0.0, 0.0],
+            [0.0, factors.y, 0.0, 0.0],
+            [0.0, 0.0, factors.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Rotate counter-clockwise by `degrees` about `axis` (which needn't be unit length),
+    /// looking back down the axis towards the origin
+    pub fn rotation(axis: Vec3, degrees: f64) -> Self {
+        let a = axis.unit_vector();
+        let (sin, cos) = degrees_to_radians(degrees).sin_cos();
+        let t = 1.0 - cos;
+        // Rodrigues' rotation formula
+        Self::new([
+            [(t * a.x * a.x) + cos, (t * a.x * a.y) - (sin * a.z), (t * a.x * a.z) + (sin * a.y), 0.0],
+            [(t * a.x * a.y) + (sin * a.z), (t * a.y * a.y) + cos, (t * a.y * a.z) - (sin * a.x), 0.0],
+            [(t * a.x * a.z) - (sin * a.y), (t * a.y * a.z) + (sin * a.x), (t * a.z * a.z) + cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut m = [[0.0; 4]; 4];
+        for (row, values) in m.iter_mut().enumerate() {
+            for (column, value) in values.iter_mut().enumerate() {
+                *value = self.m[column][row];
+            }
+        }
+        Self::new(m)
+    }
+
+    /// Gauss-Jordan elimination with partial pivoting.
+    /// Returns `None` for a singular matrix, such as one that scales an axis by zero.
+    pub fn inverse(&self) -> Option<Self> {
+        let mut m = self.m;
+        let mut inverse = Self::identity().m;
+        for column in 0..4 {
+            let pivot = (column..4).max_by(|a, b| m[*a][column].abs().total_cmp(&m[*b][column].abs()))?;
+            if m[pivot][column].abs() < 1e-12 {
+                return None;
+            }
+            m.swap(column, pivot);
+            inverse.swap(column, pivot);
+
+            let scale = 1.0 / m[column][column];
+            for i in 0..4 {
+                m[column][i] *= scale;
+                inverse[column][i] *= scale;
+            }
+            for row in 0..4 {
+                if row == column {
+                    continue;
+                }
+                let factor = m[row][column];
+                for i in 0..4 {
+                    m[row][i] -= factor * m[column][i];
+                    inverse[row][i] -= factor * inverse[column][i];
+                }
+            }
+        }
+        Some(Self::new(inverse))
+    }
+
+    pub fn transform_point(&self, p: Pos) -> Pos {
+        let m = &self.m;
+        Pos::new(
+            (m[0][0] * p.x) + (m[0][1] * p.y) + (m[0][2] * p.z) + m[0][3],
+            (m[1][0] * p.x) + (m[1][1] * p.y) + (m[1][2] * p.z) + m[1][3],
+            (m[2][0] * p.x) + (m[2][1] * p.y) + (m[2][2] * p.z) + m[2][3],
+        )
+    }
+
+    /// Directions aren't affected by translation
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3::new(
+            (m[0][0] * v.x) + (m[0][1] * v.y) + (m[0][2] * v.z),
+            (m[1][0] * v.x) + (m[1][1] * v.y) + (m[1][2] * v.z),
+            (m[2][0] * v.x) + (m[2][1] * v.y) + (m[2][2] * v.z),
+        )
+    }
+}
+
+impl Mul for Matrix4 {
+    type Output = Self;
+
+    /// The transformation applying `rhs` first, then `self`
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut m = [[0.0; 4]; 4];
+        for (row, values) in m.iter_mut().enumerate() {
+            for (column, value) in values.iter_mut().enumerate() {
+                *value = (0..4).map(|i| self.m[row][i] * rhs.m[i][column]).sum();
+            }
+        }
+        Self::new(m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near_identity(m: Matrix4) {
+        let identity = Matrix4::identity();
+        for row in 0..4 {
+            for column in 0..4 {
+                assert!((m.m[row][column] - identity.m[row][column]).abs() < 1e-9, "{m:?} is not the identity");
+            }
+        }
+    }
+
+    fn test_transforms() -> Vec<Matrix4> {
+        vec![
+            Matrix4::identity(),
+            Matrix4::translation(Vec3::new(3.0, -2.0, 0.5)),
+            Matrix4::scale(Vec3::new(2.0, 0.5, -4.0)),
+            Matrix4::rotation(Vec3::new(1.0, 2.0, 3.0), 37.0),
+            Matrix4::translation(Vec3::new(1.0, 1.0, -5.0))
+                * Matrix4::rotation(Vec3::new(0.0, 1.0, 0.0), 90.0)
+                * Matrix4::scale(Vec3::new(3.0, 3.0, 0.25)),
+            // Needs rows swapping to find a pivot
+            Matrix4::new([
+                [0.0, 2.0, 0.0, 1.0],
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 0.0, 0.0, 3.0],
+                [0.0, 0.0, 4.0, 1.0],
+            ]),
+        ]
+    }
+
+    #[test]
+    fn inverse_undoes_the_transform() {
+        for m in test_transforms() {
+            let inverse = m.inverse().expect("transform should be invertible");
+            assert_near_identity(inverse * m);
+            assert_near_identity(m * inverse);
+        }
+    }
+
+    #[test]
+    fn inverse_maps_points_back() {
+        let m = Matrix4::translation(Vec3::new(1.0, 2.0, 3.0)) * Matrix4::rotation(Vec3::new(1.0, 0.0, 1.0), 60.0);
+        let p = Pos::new(-4.0, 0.5, 2.0);
+        let round_trip = m.inverse().unwrap().transform_point(m.transform_point(p));
+        assert!((*round_trip - *p).length() < 1e-9);
+    }
+
+    #[test]
+    fn singular_matrices_have_no_inverse() {
+        assert!(Matrix4::scale(Vec3::new(1.0, 0.0, 1.0)).inverse().is_none());
+        assert!(Matrix4::new([[1.0, 2.0, 3.0, 4.0]; 4]).inverse().is_none());
+    }
+
+    #[test]
+    fn rotation_turns_counter_clockwise_about_the_axis() {
+        let m = Matrix4::rotation(Vec3::new(0.0, 0.0, 2.0), 90.0);
+        let v = m.transform_vector(Vec3::new(1.0, 0.0, 0.0));
+        assert!((v - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-12);
+    }
+}
@@ -1,17 +1,22 @@
 use std::sync::Arc;
 use crate::color::Color;
 use crate::hittable::HitRecord;
-use crate::material::Material;
+use crate::material::{Material, ScatterRecord};
 use crate::ray::Ray;
+use crate::texture::{SolidColor, Texture};
 use crate::vec3::Vec3;
 
 pub struct MetalMaterial {
-    albedo: Color,
+    albedo: Arc<dyn Texture>,
     fuzz: f64,
 }
 
 impl MetalMaterial {
     pub fn new(albedo: Color, fuzz: f64) -> Arc<Self> {
+        Self::with_texture(SolidColor::new(albedo), fuzz)
+    }
+
+    pub fn with_texture(albedo: Arc<dyn Texture>, fuzz: f64) -> Arc<Self> {
         let fuzz = if fuzz < 1.0 { fuzz } else { 1.0 };
         Arc::new(
             Self {
@@ -23,12 +28,12 @@ impl MetalMaterial {
 }
 
 impl Material for MetalMaterial {
-    fn scatter(&self, ray: Ray, hit_record: Option<&HitRecord>) -> Option<(Ray, Color)> {
-        let hit_record = hit_record.expect("Expected a hit record to be available");
+    fn scatter(&self, ray: Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
         let reflected = Vec3::reflect(ray.direction.unit_vector(), hit_record.normal);
         let fuzzed_direction = reflected + (self.fuzz * Vec3::random_unit_vector());
-        let scattered = Ray::new(hit_record.pos, fuzzed_direction);
-        Some((scattered, self.albedo))
+        let scattered = Ray::with_time(hit_record.pos, fuzzed_direction, ray.time);
+        // Fuzz blurs the reflection, but it's still treated as a mirror
+        Some(ScatterRecord::delta(scattered, self.albedo.value(hit_record.u, hit_record.v, hit_record.pos)))
     }
 }
 
@@ -0,0 +1,70 @@
+use std::sync::Arc;
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::pos::Pos;
+use crate::ray::Ray;
+use crate::sphere::{hit_sphere, sphere_bounding_box};
+
+/// The position of a moving object at a moment in time
+#[derive(Debug, Copy, Clone)]
+pub struct Keyframe {
+    pub time: f64,
+    pub center: Pos,
+}
+
+/// A sphere whose center moves in straight lines between keyframes.
+/// Before the first keyframe and after the last, it stays put.
+pub struct MovingSphere {
+    /// Ordered by time
+    keyframes: Vec<Keyframe>,
+    radius: f64,
+    material: Arc<dyn Material>,
+    bounding_box: Aabb,
+}
+
+impl MovingSphere {
+    /// Keyframes may be given in any order, but there must be at least one
+    pub fn new(
+        mut keyframes: Vec<Keyframe>,
+        radius: f64,
+        material: &Arc<dyn Material>,
+    ) -> Self {
+        assert!(!keyframes.is_empty(), "A moving sphere needs at least one keyframe");
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        // The path is straight between keyframes, so the boxes at each keyframe enclose the whole sweep
+        let bounding_box = keyframes.iter()
+            .map(|keyframe| sphere_bounding_box(keyframe.center, radius))
+            .fold(Aabb::empty(), |a, b| Aabb::enclosing(&a, &b));
+        Self {
+            keyframes,
+            radius,
+            material: Arc::clone(material),
+            bounding_box,
+        }
+    }
+
+    fn center(&self, time: f64) -> Pos {
+        let next = self.keyframes.partition_point(|keyframe| keyframe.time <= time);
+        if next == 0 {
+            return self.keyframes[0].center;
+        }
+        if next == self.keyframes.len() {
+            return self.keyframes[next - 1].center;
+        }
+        let (from, to) = (self.keyframes[next - 1], self.keyframes[next]);
+        let t = (time - from.time) / (to.time - from.time);
+        from.center + (t * (*to.center - *from.center))
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit(&self, ray: Ray, ray_t: Interval) -> Option<HitRecord> {
+        hit_sphere(self.center(ray.time), self.radius, &self.material, ray, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+}
@@ -0,0 +1,97 @@
+use std::sync::Arc;
+use crate::color::Color;
+use crate::perlin::Perlin;
+use crate::pos::Pos;
+use crate::texture::Texture;
+
+/// Octaves of noise summed for turbulence
+const TURBULENCE_DEPTH: usize = 7;
+
+fn lerp(from: Color, to: Color, t: f64) -> Color {
+    ((1.0 - t) * from) + (t * to)
+}
+
+/// Smooth gray noise. A larger `scale` gives finer detail.
+pub struct NoiseTexture {
+    noise: Perlin,
+    scale: f64,
+}
+
+impl NoiseTexture {
+    pub fn new(seed: u64, scale: f64) -> Arc<Self> {
+        Arc::new(
+            Self {
+                noise: Perlin::new(seed),
+                scale,
+            }
+        )
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, pos: Pos) -> Color {
+        let brightness = 0.5 * (1.0 + self.noise.noise((self.scale * *pos).into()));
+        brightness * Color::white()
+    }
+}
+
+/// Veins running across the Z axis, made wavy by turbulence
+pub struct MarbleTexture {
+    noise: Perlin,
+    scale: f64,
+    base: Color,
+    veins: Color,
+}
+
+impl MarbleTexture {
+    /// A larger `scale` packs the veins closer together
+    pub fn new(seed: u64, scale: f64, base: Color, veins: Color) -> Arc<Self> {
+        Arc::new(
+            Self {
+                noise: Perlin::new(seed),
+                scale,
+                base,
+                veins,
+            }
+        )
+    }
+}
+
+impl Texture for MarbleTexture {
+    fn value(&self, _u: f64, _v: f64, pos: Pos) -> Color {
+        let phase = (self.scale * pos.z) + (10.0 * self.noise.turbulence(pos, TURBULENCE_DEPTH));
+        lerp(self.veins, self.base, 0.5 * (1.0 + phase.sin()))
+    }
+}
+
+/// Growth rings around the Y axis, distorted by turbulence
+pub struct WoodTexture {
+    noise: Perlin,
+    scale: f64,
+    light: Color,
+    dark: Color,
+}
+
+impl WoodTexture {
+    /// `scale` is the number of rings per unit of distance from the axis
+    pub fn new(seed: u64, scale: f64, light: Color, dark: Color) -> Arc<Self> {
+        Arc::new(
+            Self {
+                noise: Perlin::new(seed),
+                scale,
+                light,
+                dark,
+            }
+        )
+    }
+}
+
+impl Texture for WoodTexture {
+    fn value(&self, _u: f64, _v: f64, pos: Pos) -> Color {
+        let distance = f64::hypot(pos.x, pos.z);
+        let grain = (self.scale * distance) + (0.5 * self.noise.turbulence(pos, TURBULENCE_DEPTH));
+        // Each ring fades from light early wood to dark late wood
+        let ring = grain - grain.floor();
+        lerp(self.light, self.dark, ring * ring)
+    }
+}
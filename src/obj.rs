@@ -0,0 +1,515 @@
+//! Wavefront OBJ models, and the MTL material libraries they refer to.
+//!
+//! Faces are split into one triangle mesh per group and material.
+//! MTL materials are approximated with the materials we have:
+//! - anything with an emissive color (`Ke`) becomes a `DiffuseLightMaterial`
+//! - anything transparent (`d` < 1, `Tr` > 0, or a refractive `illum` model) becomes a `DielectricMaterial` with index `Ni`
+//! - anything with a specular color (`Ks`) and no diffuse color, or with `illum 3`, becomes a `MetalMaterial`,
+//!   getting rougher as the specular exponent `Ns` falls
+//! - everything else becomes a `LambertianMaterial` colored by `Kd`
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use crate::color::Color;
+use crate::dielectric::DielectricMaterial;
+use crate::diffuse_light::DiffuseLightMaterial;
+use crate::lambertian::LambertianMaterial;
+use crate::material::Material;
+use crate::metal::MetalMaterial;
+use crate::pos::Pos;
+use crate::triangle::Uv;
+use crate::triangle_mesh::{MeshData, MeshFace, TriangleMesh};
+use crate::vec3::Vec3;
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Syntax {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+
+impl Display for ObjError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ObjError::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            ObjError::Syntax { path, line, message } => write!(f, "{}:{line}: {message}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+/// A mesh built from one group of an OBJ file
+pub struct ObjMesh {
+    pub group: String,
+    pub mesh: TriangleMesh,
+}
+
+/// Splits a file into whitespace-separated statements, reporting errors against the current line
+struct Statements<'a> {
+    path: &'a Path,
+    line: usize,
+}
+
+impl Statements<'_> {
+    fn error(&self, message: String) -> ObjError {
+        ObjError::Syntax {
+            path: self.path.to_path_buf(),
+            line: self.line,
+            message,
+        }
+    }
+
+    fn numbers<const N: usize>(&self, keyword: &str, args: &[&str], required: usize) -> Result<[f64; N], ObjError> {
+        if args.len() < required || args.len() > N {
+            let expected = if required == N { format!("{N}") } else { format!("{required} to {N}") };
+            return Err(self.error(format!("expected {expected} numbers after `{keyword}`, found {}", args.len())));
+        }
+        let mut values = [0.0; N];
+        for (value, arg) in values.iter_mut().zip(args.iter()) {
+            *value = arg.parse().map_err(|_| self.error(format!("`{arg}` is not a number")))?;
+        }
+        Ok(values)
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, ObjError> {
+    fs::read_to_string(path).map_err(|source| ObjError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Read the materials declared in an MTL file
+pub fn load_mtl(path: &Path) -> Result<HashMap<String, Arc<dyn Material>>, ObjError> {
+    #[derive(Default)]
+    struct MtlDescription {
+        diffuse: Option<Color>,
+        specular: Option<Color>,
+        specular_exponent: Option<f64>,
+        index_of_refraction: Option<f64>,
+        dissolve: Option<f64>,
+        emissive: Option<Color>,
+        illumination_model: Option<u32>,
+    }
+
+    impl MtlDescription {
+        fn build(&self) -> Arc<dyn Material> {
+            let is_black = |c: Option<Color>| c.is_none_or(|c| c.r().max(c.g()).max(c.b()) <= 0.0);
+            let is_transparent = self.dissolve.is_some_and(|d| d < 1.0)
+                || matches!(self.illumination_model, Some(4 | 6 | 7 | 9));
+
+            if !is_black(self.emissive) {
+                DiffuseLightMaterial::new(self.emissive.unwrap())
+            }
+            else if is_transparent {
+                DielectricMaterial::new(self.index_of_refraction.unwrap_or(1.5))
+            }
+            else if !is_black(self.specular) && (is_black(self.diffuse) || self.illumination_model == Some(3)) {
+                // Map the Phong exponent onto a roughness, with 0 being a perfect mirror
+                let exponent = self.specular_exponent.unwrap_or(0.0).max(0.0);
+                let fuzz = (2.0 / (exponent + 2.0)).sqrt();
+                MetalMaterial::new(self.specular.unwrap(), fuzz)
+            }
+            else {
+                LambertianMaterial::new(self.diffuse.unwrap_or(Color::new(0.8, 0.8, 0.8)))
+            }
+        }
+    }
+
+    let text = read_file(path)?;
+    let mut statements = Statements { path, line: 0 };
+    let mut descriptions: Vec<(String, MtlDescription)> = vec![];
+
+    for (i, line) in text.lines().enumerate() {
+        statements.line = i + 1;
+        let mut words = line.split_whitespace();
+        let Some(keyword) = words.next() else {
+            continue;
+        };
+        if keyword.starts_with('#') {
+            continue;
+        }
+        let args: Vec<&str> = words.collect();
+
+        if keyword == "newmtl" {
+            let name = args.join(" ");
+            if name.is_empty() {
+                return Err(statements.error("`newmtl` needs a material name".to_string()));
+            }
+            descriptions.push((name, MtlDescription::default()));
+            continue;
+        }
+
+        // Everything else describes the most recently declared material
+        let Some((_, description)) = descriptions.last_mut() else {
+            if ["Kd", "Ks", "Ns", "Ni", "d", "Tr", "Ke", "illum"].contains(&keyword) {
+                return Err(statements.error(format!("`{keyword}` appears before any `newmtl`")));
+            }
+            continue;
+        };
+        let color = |statements: &Statements, keyword: &str| {
+            statements.numbers::<3>(keyword, &args, 3).map(|[r, g, b]| Color::new(r, g, b))
+        };
+        match keyword {
+            "Kd" => description.diffuse = Some(color(&statements, keyword)?),
+            "Ks" => description.specular = Some(color(&statements, keyword)?),
+            "Ke" => description.emissive = Some(color(&statements, keyword)?),
+            "Ns" => description.specular_exponent = Some(statements.numbers::<1>(keyword, &args, 1)?[0]),
+            "Ni" => description.index_of_refraction = Some(statements.numbers::<1>(keyword, &args, 1)?[0]),
+            "d" => description.dissolve = Some(statements.numbers::<1>(keyword, &args, 1)?[0]),
+            // Transparency is the opposite of dissolve
+            "Tr" => description.dissolve = Some(1.0 - statements.numbers::<1>(keyword, &args, 1)?[0]),
+            "illum" => {
+                let model = statements.numbers::<1>(keyword, &args, 1)?[0];
+                description.illumination_model = Some(model as u32);
+            }
+            // Texture maps and other properties we can't represent
+            _ => {}
+        }
+    }
+
+    Ok(descriptions.into_iter().map(|(name, description)| (name, description.build())).collect())
+}
+
+/// Faces collected for one group and material, indexing into the whole file's vertex buffers
+struct FaceGroup {
+    group: String,
+    material: Arc<dyn Material>,
+    faces: Vec<MeshFace>,
+}
+
+/// Load a model, with one mesh for each group and material used in the file.
+/// Faces before any `usemtl` statement use `default_material`.
+pub fn load_obj(path: &Path, default_material: &Arc<dyn Material>) -> Result<Vec<ObjMesh>, ObjError> {
+    let text = read_file(path)?;
+    let mut statements = Statements { path, line: 0 };
+
+    let mut positions: Vec<Pos> = vec![];
+    let mut normals: Vec<Vec3> = vec![];
+    let mut uvs: Vec<Uv> = vec![];
+    let mut materials: HashMap<String, Arc<dyn Material>> = HashMap::new();
+
+    let mut face_groups: Vec<FaceGroup> = vec![];
+    let mut current_group = "default".to_string();
+    let mut current_material = Arc::clone(default_material);
+    // Whether the next face should start a new mesh
+    let mut group_changed = true;
+
+    for (i, line) in text.lines().enumerate() {
+        statements.line = i + 1;
+        let mut words = line.split_whitespace();
+        let Some(keyword) = words.next() else {
+            continue;
+        };
+        if keyword.starts_with('#') {
+            continue;
+        }
+        let args: Vec<&str> = words.collect();
+
+        match keyword {
+            "v" => {
+                // Either an optional fourth (w) component, or the common `v x y z r g b` vertex color extension, both ignored
+                let [x, y, z, ..] = statements.numbers::<7>(keyword, &args, 3)?;
+                positions.push(Pos::new(x, y, z));
+            }
+            "vn" => {
+                let [x, y, z] = statements.numbers::<3>(keyword, &args, 3)?;
+                normals.push(Vec3::new(x, y, z).unit_vector());
+            }
+            "vt" => {
+                let [u, v, _] = statements.numbers::<3>(keyword, &args, 1)?;
+                uvs.push((u, v));
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(statements.error(format!("a face needs at least 3 vertices, found {}", args.len())));
+                }
+                let vertices = args.iter()
+                    .map(|arg| parse_face_vertex(&statements, arg, positions.len(), uvs.len(), normals.len()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                // Attributes are all-or-nothing across a face
+                let has_uvs = vertices.iter().all(|(_, uv, _)| uv.is_some());
+                let has_normals = vertices.iter().all(|(_, _, normal)| normal.is_some());
+
+                if group_changed {
+                    face_groups.push(FaceGroup {
+                        group: current_group.clone(),
+                        material: Arc::clone(&current_material),
+                        faces: vec![],
+                    });
+                    group_changed = false;
+                }
+                let faces = &mut face_groups.last_mut().unwrap().faces;
+
+                // Triangulate polygons as a fan around the first vertex
+                for corner in 1..(vertices.len() - 1) {
+                    let triangle = [vertices[0], vertices[corner], vertices[corner + 1]];
+                    faces.push(MeshFace {
+                        positions: triangle.map(|(position, _, _)| position),
+                        uvs: has_uvs.then(|| triangle.map(|(_, uv, _)| uv.unwrap())),
+                        normals: has_normals.then(|| triangle.map(|(_, _, normal)| normal.unwrap())),
+                    });
+                }
+            }
+            "g" | "o" => {
+                current_group = if args.is_empty() { "default".to_string() } else { args.join(" ") };
+                group_changed = true;
+            }
+            "usemtl" => {
+                let name = args.join(" ");
+                current_material = match materials.get(&name) {
+                    Some(material) => Arc::clone(material),
+                    None => return Err(statements.error(format!("material `{name}` isn't defined in any `mtllib` loaded so far"))),
+                };
+                group_changed = true;
+            }
+            "mtllib" => {
+                // Libraries are found relative to the model
+                for library in args.iter() {
+                    let library_path = path.parent().unwrap_or(Path::new("")).join(library);
+                    materials.extend(load_mtl(&library_path)?);
+                }
+            }
+            // Smoothing groups, lines, points, free-form surfaces and so on don't affect triangle meshes
+            _ => {}
+        }
+    }
+
+    // Give each mesh only the vertices it uses
+    let mut meshes = vec![];
+    for face_group in face_groups.into_iter().filter(|face_group| !face_group.faces.is_empty()) {
+        let mut mesh_positions = IndexRemap::new();
+        let mut mesh_normals = IndexRemap::new();
+        let mut mesh_uvs = IndexRemap::new();
+        let faces = face_group.faces.iter().map(|face| MeshFace {
+            positions: face.positions.map(|i| mesh_positions.remap(i)),
+            normals: face.normals.map(|indexes| indexes.map(|i| mesh_normals.remap(i))),
+            uvs: face.uvs.map(|indexes| indexes.map(|i| mesh_uvs.remap(i))),
+        }).collect();
+        let mesh = TriangleMesh::new(MeshData {
+            positions: mesh_positions.gather(&positions),
+            normals: mesh_normals.gather(&normals),
+            uvs: mesh_uvs.gather(&uvs),
+            faces,
+            material: face_group.material,
+        }).expect("Face indexes should have been validated while parsing");
+        meshes.push(ObjMesh {
+            group: face_group.group,
+            mesh,
+        });
+    }
+    Ok(meshes)
+}
+
+/// Parse one `v`, `v/vt`, `v//vn` or `v/vt/vn` face vertex into zero-based indexes
+fn parse_face_vertex(
+    statements: &Statements,
+    arg: &str,
+    position_count: usize,
+    uv_count: usize,
+    normal_count: usize,
+) -> Result<(usize, Option<usize>, Option<usize>), ObjError> {
+    let mut parts = arg.split('/');
+    let mut index = |name: &str, count: usize, required: bool| -> Result<Option<usize>, ObjError> {
+        let part = parts.next().unwrap_or("");
+        if part.is_empty() {
+            return match required {
+                true => Err(statements.error(format!("face vertex `{arg}` has no {name} index"))),
+                false => Ok(None),
+            };
+        }
+        let index: i64 = part.parse().map_err(|_| statements.error(format!("`{part}` in face vertex `{arg}` is not an index")))?;
+        // Indexes count from 1, or back from the most recent vertex if negative
+        let resolved = match index {
+            1.. => index - 1,
+            ..=-1 => count as i64 + index,
+            0 => return Err(statements.error(format!("face vertex `{arg}` uses index 0, but indexes start at 1"))),
+        };
+        if resolved < 0 || resolved >= count as i64 {
+            return Err(statements.error(format!("face vertex `{arg}` refers to {name} {index}, but only {count} are defined")));
+        }
+        Ok(Some(resolved as usize))
+    };
+    let position = index("position", position_count, true)?.unwrap();
+    let uv = index("texture coordinate", uv_count, false)?;
+    let normal = index("normal", normal_count, false)?;
+    Ok((position, uv, normal))
+}
+
+/// Renumbers the indexes used by one mesh, so it only keeps the vertices it refers to
+struct IndexRemap {
+    new_indexes: HashMap<usize, usize>,
+    old_indexes: Vec<usize>,
+}
+
+impl IndexRemap {
+    fn new() -> Self {
+        Self {
+            new_indexes: HashMap::new(),
+            old_indexes: vec![],
+        }
+    }
+
+    fn remap(&mut self, old_index: usize) -> usize {
+        *self.new_indexes.entry(old_index).or_insert_with(|| {
+            self.old_indexes.push(old_index);
+            self.old_indexes.len() - 1
+        })
+    }
+
+    fn gather<T: Copy>(&self, values: &[T]) -> Vec<T> {
+        self.old_indexes.iter().map(|i| values[*i]).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::Hittable;
+    use crate::interval::Interval;
+    use crate::ray::Ray;
+
+    /// Write files into a directory of their own, returning the path of the first
+    fn write_files(test_name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("raytracer-obj-{}-{test_name}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        for (name, contents) in files {
+            fs::write(directory.join(name), contents).unwrap();
+        }
+        directory.join(files[0].0)
+    }
+
+    fn default_material() -> Arc<dyn Material> {
+        LambertianMaterial::new(Color::new(0.5, 0.5, 0.5))
+    }
+
+    /// Whether a ray straight down the Z axis through (x, y) hits the mesh
+    fn hits_at(mesh: &TriangleMesh, x: f64, y: f64) -> bool {
+        let ray = Ray::new(Pos::new(x, y, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        mesh.hit(ray, Interval::new(0.001, f64::INFINITY)).is_some()
+    }
+
+    fn load_error(test_name: &str, files: &[(&str, &str)]) -> String {
+        let path = write_files(test_name, files);
+        let error = load_obj(&path, &default_material()).err().expect("model should fail to load");
+        error.to_string().replace(&path.display().to_string(), files[0].0)
+    }
+
+    #[test]
+    fn triangulates_polygons_as_a_fan() {
+        let path = write_files("fan", &[("square.obj", "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 4\n")]);
+        let meshes = load_obj(&path, &default_material()).unwrap();
+        assert_eq!(meshes.len(), 1);
+        assert_eq!(meshes[0].group, "default");
+        assert!(hits_at(&meshes[0].mesh, 0.75, 0.25));
+        assert!(hits_at(&meshes[0].mesh, 0.25, 0.75));
+        assert!(!hits_at(&meshes[0].mesh, 1.5, 0.5));
+    }
+
+    #[test]
+    fn ignores_vertex_colors() {
+        let path = write_files("colors", &[("colors.obj", "v 0 0 0 1 0 0\nv 1 0 0 0 1 0\nv 0 1 0 1 0 0 1\nf 1 2 3\n")]);
+        let meshes = load_obj(&path, &default_material()).unwrap();
+        assert!(hits_at(&meshes[0].mesh, 0.25, 0.25));
+    }
+
+    #[test]
+    fn resolves_negative_indexes_and_attributes() {
+        let model = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 0 1\nvn 0 0 1\nf -3/1/1 -2/2/1 -1/3/1\n";
+        let path = write_files("negative", &[("negative.obj", model)]);
+        let meshes = load_obj(&path, &default_material()).unwrap();
+        assert!(hits_at(&meshes[0].mesh, 0.25, 0.25));
+    }
+
+    #[test]
+    fn splits_meshes_by_group_and_material() {
+        let model = "\
+mtllib lights.mtl
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 2 0 0
+v 3 0 0
+v 2 1 0
+g left
+f 1 2 3
+g right
+usemtl lamp
+f 4 5 6
+";
+        let path = write_files("groups", &[("groups.obj", model), ("lights.mtl", "newmtl lamp\nKe 4 4 4\n")]);
+        let meshes = load_obj(&path, &default_material()).unwrap();
+        let groups: Vec<&str> = meshes.iter().map(|mesh| mesh.group.as_str()).collect();
+        assert_eq!(groups, ["left", "right"]);
+        assert!(hits_at(&meshes[0].mesh, 0.25, 0.25) && !hits_at(&meshes[0].mesh, 2.25, 0.25));
+        assert!(hits_at(&meshes[1].mesh, 2.25, 0.25) && !hits_at(&meshes[1].mesh, 0.25, 0.25));
+
+        let ray = Ray::new(Pos::new(2.25, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit_record = meshes[1].mesh.hit(ray, Interval::new(0.001, f64::INFINITY)).unwrap();
+        assert_eq!(hit_record.material.emitted(&hit_record), Color::new(4.0, 4.0, 4.0));
+    }
+
+    #[test]
+    fn reports_bad_statements_by_line() {
+        assert_eq!(
+            load_error("few_numbers", &[("m.obj", "# a comment\nv 1 2\n")]),
+            "m.obj:2: expected 3 to 7 numbers after `v`, found 2",
+        );
+        assert_eq!(
+            load_error("many_numbers", &[("m.obj", "v 1 2 3 4 5 6 7 8\n")]),
+            "m.obj:1: expected 3 to 7 numbers after `v`, found 8",
+        );
+        assert_eq!(load_error("not_number", &[("m.obj", "vn 1 2 z\n")]), "m.obj:1: `z` is not a number");
+        assert_eq!(
+            load_error("small_face", &[("m.obj", "v 0 0 0\nv 1 0 0\nf 1 2\n")]),
+            "m.obj:3: a face needs at least 3 vertices, found 2",
+        );
+    }
+
+    #[test]
+    fn reports_bad_face_indexes() {
+        let vertices = "v 0 0 0\nv 1 0 0\nv 0 1 0\n";
+        assert_eq!(
+            load_error("zero_index", &[("m.obj", &format!("{vertices}f 0 1 2\n"))]),
+            "m.obj:4: face vertex `0` uses index 0, but indexes start at 1",
+        );
+        assert_eq!(
+            load_error("out_of_range", &[("m.obj", &format!("{vertices}f 1 2 4\n"))]),
+            "m.obj:4: face vertex `4` refers to position 4, but only 3 are defined",
+        );
+        assert_eq!(
+            load_error("missing_normal", &[("m.obj", &format!("{vertices}f 1//1 2//1 3//1\n"))]),
+            "m.obj:4: face vertex `1//1` refers to normal 1, but only 0 are defined",
+        );
+        assert_eq!(
+            load_error("bad_index", &[("m.obj", &format!("{vertices}f 1 2 three\n"))]),
+            "m.obj:4: `three` in face vertex `three` is not an index",
+        );
+    }
+
+    #[test]
+    fn reports_unknown_materials() {
+        assert_eq!(
+            load_error("unknown_material", &[("m.obj", "usemtl gold\n")]),
+            "m.obj:1: material `gold` isn't defined in any `mtllib` loaded so far",
+        );
+        let error = load_error("missing_library", &[("m.obj", "mtllib missing.mtl\n")]);
+        assert!(error.contains("missing.mtl: "), "{error}");
+    }
+
+    #[test]
+    fn reports_mtl_properties_outside_a_material() {
+        let path = write_files("orphan", &[("orphan.mtl", "Kd 1 0 0\n")]);
+        let error = load_mtl(&path).err().unwrap().to_string();
+        assert_eq!(error, format!("{}:1: `Kd` appears before any `newmtl`", path.display()));
+    }
+}
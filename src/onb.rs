@@ -0,0 +1,35 @@
+use crate::vec3::Vec3;
+
+/// Orthonormal basis: three perpendicular unit vectors, with `w` along a chosen direction
+#[derive(Debug, Copy, Clone)]
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl Onb {
+    /// A basis whose `w` points along `direction`. The other two axes are arbitrary, but always the same for the same direction.
+    pub fn new(direction: Vec3) -> Self {
+        let w = direction.unit_vector();
+        // Any vector not parallel to `w` will do to build the others from
+        let helper = if w.x.abs() > 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+        let v = Vec3::cross(&w, &helper).unit_vector();
+        let u = Vec3::cross(&w, &v);
+        Self { u, v, w }
+    }
+
+    /// Convert coordinates in this basis to a vector in world space
+    pub fn local(&self, a: f64, b: f64, c: f64) -> Vec3 {
+        (a * self.u) + (b * self.v) + (c * self.w)
+    }
+
+    /// Convert a world space vector to coordinates in this basis
+    pub fn coordinates(&self, direction: Vec3) -> Vec3 {
+        Vec3::new(
+            Vec3::dot(direction, self.u),
+            Vec3::dot(direction, self.v),
+            Vec3::dot(direction, self.w),
+        )
+    }
+}
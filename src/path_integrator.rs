@@ -0,0 +1,67 @@
+use crate::color::Color;
+use crate::integrator::{emitted_light, sample_lights, Integrator, SceneView};
+use crate::ray::Ray;
+use crate::utils::rand_proportion;
+
+/// Path tracing: follows each camera ray as it bounces around the scene,
+/// gathering light from emitters hit along the way and from lights sampled at every diffuse bounce
+pub struct PathIntegrator {
+    max_ray_bounces: usize,
+    /// Bounces after which paths may be ended early, the more likely the less light they carry
+    russian_roulette_depth: usize,
+}
+
+impl PathIntegrator {
+    /// Paths are ended at random after `russian_roulette_depth` bounces, with a chance that grows as their throughput drops.
+    /// Paths that carry on are weighted up to match, so this saves work without biasing the image.
+    pub fn new(max_ray_bounces: usize, russian_roulette_depth: usize) -> Self {
+        Self {
+            max_ray_bounces,
+            russian_roulette_depth,
+        }
+    }
+}
+
+impl Integrator for PathIntegrator {
+    fn ray_color(&self, ray: Ray, scene: &SceneView) -> (Color, usize) {
+        let mut color = Color::black();
+        // How much of the light found further along the path makes it back to the camera
+        let mut throughput = Color::white();
+        let mut ray = ray;
+        let mut bsdf_pdf = None;
+        let mut path_length = 0;
+
+        // Once we've reached the ray bounce limit, no more light is contributed
+        while path_length < self.max_ray_bounces {
+            path_length += 1;
+            let Some(hit_record) = scene.hit(ray) else {
+                color += throughput * scene.background.background(ray);
+                break;
+            };
+            color += throughput * emitted_light(scene, ray, &hit_record, bsdf_pdf);
+
+            let Some(scatter) = hit_record.material.scatter(ray, &hit_record) else {
+                break;
+            };
+            if scatter.is_delta {
+                bsdf_pdf = None;
+            }
+            else {
+                color += throughput * sample_lights(scene, ray, &hit_record);
+                bsdf_pdf = Some(scatter.pdf);
+            }
+            throughput = throughput * scatter.weight();
+            ray = scatter.ray;
+
+            if path_length >= self.russian_roulette_depth {
+                // Dim paths are likely to end here, and the survivors make up for them
+                let survival = throughput.r().max(throughput.g()).max(throughput.b()).min(0.95);
+                if rand_proportion() >= survival {
+                    break;
+                }
+                throughput = (1.0 / survival) * throughput;
+            }
+        }
+        (color, path_length)
+    }
+}
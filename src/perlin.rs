@@ -0,0 +1,99 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use crate::pos::Pos;
+use crate::vec3::Vec3;
+
+const POINT_COUNT: usize = 256;
+
+/// Gradient noise: smooth pseudo-random variation across space.
+/// Two generators built from the same seed produce the same noise.
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    permute_x: Vec<usize>,
+    permute_y: Vec<usize>,
+    permute_z: Vec<usize>,
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        // Use a generator of our own, so the noise doesn't depend on what else was drawn before it
+        let mut rng = StdRng::seed_from_u64(seed);
+        let gradients = (0..POINT_COUNT)
+            .map(|_| {
+                Vec3::new(
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                ).unit_vector()
+            })
+            .collect();
+        let permutation = |rng: &mut StdRng| {
+            let mut indexes: Vec<usize> = (0..POINT_COUNT).collect();
+            indexes.shuffle(rng);
+            indexes
+        };
+        Self {
+            gradients,
+            permute_x: permutation(&mut rng),
+            permute_y: permutation(&mut rng),
+            permute_z: permutation(&mut rng),
+        }
+    }
+
+    /// Noise at `p`, in roughly [-1, 1]
+    pub fn noise(&self, p: Pos) -> f64 {
+        let (i, j, k) = (p.x.floor() as i64, p.y.floor() as i64, p.z.floor() as i64);
+        let (u, v, w) = (p.x - p.x.floor(), p.y - p.y.floor(), p.z - p.z.floor());
+
+        // The gradient at each corner of the lattice cell containing `p`
+        let mut corners = [[[Vec3::zero(); 2]; 2]; 2];
+        for (di, plane) in corners.iter_mut().enumerate() {
+            for (dj, row) in plane.iter_mut().enumerate() {
+                for (dk, corner) in row.iter_mut().enumerate() {
+                    let index = self.permute_x[((i + di as i64) & 255) as usize]
+                        ^ self.permute_y[((j + dj as i64) & 255) as usize]
+                        ^ self.permute_z[((k + dk as i64) & 255) as usize];
+                    *corner = self.gradients[index];
+                }
+            }
+        }
+        perlin_interpolate(&corners, u, v, w)
+    }
+
+    /// Sum of `depth` octaves of noise, each at double the frequency and half the weight of the last.
+    /// Always positive, giving the look of turbulent flow.
+    pub fn turbulence(&self, p: Pos, depth: usize) -> f64 {
+        let mut accumulated = 0.0;
+        let mut p: Vec3 = p.into();
+        let mut weight = 1.0;
+        for _ in 0..depth {
+            accumulated += weight * self.noise(p.into());
+            weight *= 0.5;
+            p *= 2.0;
+        }
+        accumulated.abs()
+    }
+}
+
+/// Trilinearly blend the dot products of each corner's gradient with the offset to `(u, v, w)`,
+/// easing the weights with a Hermite cubic so the noise has no visible lattice edges
+fn perlin_interpolate(corners: &[[[Vec3; 2]; 2]; 2], u: f64, v: f64, w: f64) -> f64 {
+    let hermite = |t: f64| t * t * (3.0 - (2.0 * t));
+    let (uu, vv, ww) = (hermite(u), hermite(v), hermite(w));
+
+    let mut accumulated = 0.0;
+    for (i, plane) in corners.iter().enumerate() {
+        for (j, row) in plane.iter().enumerate() {
+            for (k, gradient) in row.iter().enumerate() {
+                let (i, j, k) = (i as f64, j as f64, k as f64);
+                let offset = Vec3::new(u - i, v - j, w - k);
+                accumulated += ((i * uu) + ((1.0 - i) * (1.0 - uu)))
+                    * ((j * vv) + ((1.0 - j) * (1.0 - vv)))
+                    * ((k * ww) + ((1.0 - k) * (1.0 - ww)))
+                    * Vec3::dot(*gradient, offset);
+            }
+        }
+    }
+    accumulated
+}
@@ -0,0 +1,61 @@
+use crate::framebuffer::Framebuffer;
+
+/// Portable float map: linear, unclamped 32-bit floats per component
+pub fn encode_pfm(framebuffer: &Framebuffer) -> Vec<u8> {
+    let mut out = vec![];
+    // A negative scale marks the data as little-endian
+    out.extend(format!("PF\n{} {}\n-1.0\n", framebuffer.width(), framebuffer.height()).as_bytes());
+    // Scanlines are stored bottom to top
+    for y in (0..framebuffer.height()).rev() {
+        for pixel in framebuffer.row(y) {
+            for component in [pixel.r(), pixel.g(), pixel.b()] {
+                out.extend((component as f32).to_le_bytes());
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn decode_pfm(bytes: &[u8]) -> Framebuffer {
+        let header: Vec<&[u8]> = bytes.splitn(4, |byte| *byte == b'\n').collect();
+        assert_eq!(header[0], b"PF");
+        let size = String::from_utf8_lossy(header[1]);
+        let (width, height) = size.split_once(' ').unwrap();
+        let (width, height): (usize, usize) = (width.parse().unwrap(), height.parse().unwrap());
+        assert_eq!(header[2], b"-1.0");
+
+        let mut floats = header[3].chunks_exact(4).map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()) as f64);
+        let mut framebuffer = Framebuffer::new(width, height);
+        for y in (0..height).rev() {
+            for pixel in framebuffer.row_mut(y) {
+                *pixel = Color::new(floats.next().unwrap(), floats.next().unwrap(), floats.next().unwrap());
+            }
+        }
+        assert!(floats.next().is_none(), "unexpected data after the last pixel");
+        framebuffer
+    }
+
+    #[test]
+    fn round_trips_through_32_bit_floats() {
+        let mut framebuffer = Framebuffer::new(3, 2);
+        for y in 0..2 {
+            for x in 0..3 {
+                framebuffer.row_mut(y)[x] = Color::new(x as f64 * 10.5, y as f64 - 0.25, 1e-3 * (x + y) as f64);
+            }
+        }
+        let decoded = decode_pfm(&encode_pfm(&framebuffer));
+        assert_eq!((decoded.width(), decoded.height()), (3, 2));
+        for y in 0..2 {
+            for (expected, actual) in framebuffer.row(y).iter().zip(decoded.row(y)) {
+                for component in 0..3 {
+                    assert_eq!(expected[component] as f32 as f64, actual[component]);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::sync::Arc;
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::onb::Onb;
+use crate::pos::Pos;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+/// An infinite plane through `point`, whose front face is the side `normal` points towards.
+/// Surface coordinates are distances across the plane from `point`, so textures repeat across it
+/// with wrapping addressing.
+pub struct Plane {
+    point: Pos,
+    basis: Onb,
+    material: Arc<dyn Material>,
+}
+
+impl Plane {
+    pub fn new(
+        point: Pos,
+        normal: Vec3,
+        material: &Arc<dyn Material>,
+    ) -> Self {
+        Self {
+            point,
+            basis: Onb::new(normal),
+            material: Arc::clone(material),
+        }
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, ray: Ray, ray_t: Interval) -> Option<HitRecord> {
+        let denominator = Vec3::dot(self.basis.w, ray.direction);
+        // The ray is parallel to the plane
+        if denominator.abs() < 1e-8 {
+            return None;
+        }
+        let t = Vec3::dot(self.basis.w, *self.point - *ray.origin) / denominator;
+        if !ray_t.surrounds(t) {
+            return None;
+        }
+
+        let hit_pos = ray.at(t);
+        let offset = *hit_pos - *self.point;
+        let (u, v) = (Vec3::dot(offset, self.basis.u), Vec3::dot(offset, self.basis.v));
+        Some(HitRecord::new(t, hit_pos, ray, self.basis.w, &self.material).with_uv(u, v))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::unbounded()
+    }
+}
@@ -0,0 +1,45 @@
+use std::io::Cursor;
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+
+/// Decode a PNG of any color type and bit depth.
+/// Components are scaled to [0, 1] but otherwise left as stored, and any alpha channel is dropped.
+pub fn decode_png(bytes: &[u8]) -> Result<Framebuffer, String> {
+    let mut decoder = png::Decoder::new(Cursor::new(bytes));
+    // Expand palettes and sub-byte depths, so every sample is 8 or 16 bits
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder.read_info().map_err(|e| format!("invalid PNG: {e}"))?;
+    let buffer_size = reader.output_buffer_size().ok_or("image is too large")?;
+    let mut buffer = vec![0; buffer_size];
+    let info = reader.next_frame(&mut buffer).map_err(|e| format!("invalid PNG: {e}"))?;
+
+    let channels = info.color_type.samples();
+    let (sample_bytes, max_value) = match info.bit_depth {
+        png::BitDepth::Sixteen => (2, 65535.0),
+        _ => (1, 255.0),
+    };
+    let sample = |row: &[u8], index: usize| -> f64 {
+        let value = match sample_bytes {
+            2 => u16::from_be_bytes([row[index * 2], row[(index * 2) + 1]]) as f64,
+            _ => row[index] as f64,
+        };
+        value / max_value
+    };
+
+    let (width, height) = (info.width as usize, info.height as usize);
+    let mut framebuffer = Framebuffer::new(width, height);
+    for y in 0..height {
+        let row = &buffer[(y * info.line_size)..((y + 1) * info.line_size)];
+        for x in 0..width {
+            let first = x * channels;
+            framebuffer.row_mut(y)[x] = match info.color_type {
+                png::ColorType::Grayscale | png::ColorType::GrayscaleAlpha => {
+                    let gray = sample(row, first);
+                    Color::new(gray, gray, gray)
+                }
+                _ => Color::new(sample(row, first), sample(row, first + 1), sample(row, first + 2)),
+            };
+        }
+    }
+    Ok(framebuffer)
+}
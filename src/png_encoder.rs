@@ -0,0 +1,29 @@
+use std::io;
+use png::{BitDepth, ColorType, Encoder};
+use crate::framebuffer::Framebuffer;
+use crate::image_format::quantize_component;
+
+/// RGB PNG, gamma-corrected and quantized the same way as the PPM output
+pub fn encode_png(framebuffer: &Framebuffer, bit_depth: BitDepth) -> io::Result<Vec<u8>> {
+    let mut data = vec![];
+    for y in 0..framebuffer.height() {
+        for pixel in framebuffer.row(y) {
+            for component in [pixel.r(), pixel.g(), pixel.b()] {
+                match bit_depth {
+                    BitDepth::Sixteen => data.extend((quantize_component(component, 65536) as u16).to_be_bytes()),
+                    BitDepth::Eight => data.push(quantize_component(component, 256) as u8),
+                    _ => panic!("Unsupported PNG bit depth {bit_depth:?}"),
+                }
+            }
+        }
+    }
+
+    let mut out = vec![];
+    let mut encoder = Encoder::new(&mut out, framebuffer.width() as u32, framebuffer.height() as u32);
+    encoder.set_color(ColorType::Rgb);
+    encoder.set_depth(bit_depth);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(&data).map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)?;
+    Ok(out)
+}
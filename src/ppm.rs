@@ -0,0 +1,224 @@
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::image_format::quantize_component;
+
+/// Plain-text PPM, one line per scanline
+pub fn encode_ppm_ascii(framebuffer: &Framebuffer) -> Vec<u8> {
+    let mut out = vec![];
+    // Write out the PPM header
+    out.extend(format!("P3\n{} {}\n255\n", framebuffer.width(), framebuffer.height()).as_bytes());
+    for y in 0..framebuffer.height() {
+        for pixel in framebuffer.row(y) {
+            out.extend(
+                format!(
+                    "{} {} {}    ",
+                    quantize_component(pixel.r(), 256),
+                    quantize_component(pixel.g(), 256),
+                    quantize_component(pixel.b(), 256),
+                ).as_bytes()
+            );
+        }
+        out.extend("\n".as_bytes());
+    }
+    out
+}
+
+/// Binary PPM. A `max_value` above 255 stores each component in two big-endian bytes.
+pub fn encode_ppm_binary(framebuffer: &Framebuffer, max_value: u32) -> Vec<u8> {
+    let mut out = vec![];
+    out.extend(format!("P6\n{} {}\n{}\n", framebuffer.width(), framebuffer.height(), max_value).as_bytes());
+    for y in 0..framebuffer.height() {
+        for pixel in framebuffer.row(y) {
+            for component in [pixel.r(), pixel.g(), pixel.b()] {
+                let value = quantize_component(component, max_value + 1);
+                if max_value > 255 {
+                    out.extend((value as u16).to_be_bytes());
+                }
+                else {
+                    out.push(value as u8);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Decode a plain-text (P3) or binary (P6) PPM, in 8 or 16 bits.
+/// Components are scaled to [0, 1] but otherwise left as stored.
+pub fn decode_ppm(bytes: &[u8]) -> Result<Framebuffer, String> {
+    let mut reader = PpmReader { bytes, offset: 0 };
+    let magic = reader.token()?;
+    if magic != "P3" && magic != "P6" {
+        return Err(format!("unsupported PPM type `{magic}`, expected P3 or P6"));
+    }
+    let width = reader.number("width")?;
+    let height = reader.number("height")?;
+    let max_value = reader.number("maximum value")?;
+    if max_value == 0 || max_value > 65535 {
+        return Err(format!("maximum value {max_value} is out of range"));
+    }
+
+    if magic == "P6" {
+        // A single whitespace byte separates the header from the binary pixels
+        reader.byte()?;
+    }
+
+    // Check the header against the data before allocating, so a corrupt size can't ask for an enormous image.
+    // Plain-text components take at least a digit each.
+    let bytes_per_component = if magic == "P6" && max_value > 255 { 2 } else { 1 };
+    let required_bytes = width.checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(3 * bytes_per_component))
+        .ok_or(format!("image size {width}x{height} is too large"))?;
+    let remaining_bytes = bytes.len() - reader.offset;
+    if required_bytes > remaining_bytes {
+        return Err(format!("a {width}x{height} image needs at least {required_bytes} bytes of pixels, but only {remaining_bytes} follow the header"));
+    }
+
+    let mut framebuffer = Framebuffer::new(width, height);
+    let next_component = |reader: &mut PpmReader| -> Result<f64, String> {
+        let value = match magic.as_str() {
+            "P3" => reader.number("pixel component")?,
+            _ if max_value > 255 => u16::from_be_bytes([reader.byte()?, reader.byte()?]) as usize,
+            _ => reader.byte()? as usize,
+        };
+        Ok(value as f64 / max_value as f64)
+    };
+    for y in 0..height {
+        for x in 0..width {
+            let r = next_component(&mut reader)?;
+            let g = next_component(&mut reader)?;
+            let b = next_component(&mut reader)?;
+            framebuffer.row_mut(y)[x] = Color::new(r, g, b);
+        }
+    }
+    Ok(framebuffer)
+}
+
+struct PpmReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl PpmReader<'_> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let byte = *self.bytes.get(self.offset).ok_or("file ends before all pixels were read")?;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    /// The next whitespace-separated token, skipping `#` comments
+    fn token(&mut self) -> Result<String, String> {
+        loop {
+            match self.bytes.get(self.offset) {
+                Some(b'#') => {
+                    while self.bytes.get(self.offset).is_some_and(|byte| *byte != b'\n') {
+                        self.offset += 1;
+                    }
+                }
+                Some(byte) if byte.is_ascii_whitespace() => self.offset += 1,
+                Some(_) => break,
+                None => return Err("file ends before all pixels were read".to_string()),
+            }
+        }
+        let start = self.offset;
+        while self.bytes.get(self.offset).is_some_and(|byte| !byte.is_ascii_whitespace()) {
+            self.offset += 1;
+        }
+        Ok(String::from_utf8_lossy(&self.bytes[start..self.offset]).into_owned())
+    }
+
+    fn number(&mut self, what: &str) -> Result<usize, String> {
+        let token = self.token()?;
+        token.parse().map_err(|_| format!("invalid {what} `{token}`"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_image() -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(4, 3);
+        for y in 0..3 {
+            for x in 0..4 {
+                framebuffer.row_mut(y)[x] = Color::new(x as f64 / 3.0, y as f64 / 2.0, 0.1 * (x + y) as f64);
+            }
+        }
+        // Out of range components are clamped
+        framebuffer.row_mut(0)[0] = Color::new(-1.0, 2.0, 0.0);
+        framebuffer
+    }
+
+    /// Decoding gives back the gamma-corrected components, to the precision they were stored with
+    fn assert_round_trips(encoded: &[u8], max_value: u32) {
+        let framebuffer = test_image();
+        let decoded = decode_ppm(encoded).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (framebuffer.width(), framebuffer.height()));
+        for y in 0..framebuffer.height() {
+            for (expected, actual) in framebuffer.row(y).iter().zip(decoded.row(y)) {
+                for component in 0..3 {
+                    let stored = quantize_component(expected[component], max_value + 1);
+                    assert_eq!(actual[component], stored as f64 / max_value as f64);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips_plain_text() {
+        assert_round_trips(&encode_ppm_ascii(&test_image()), 255);
+    }
+
+    #[test]
+    fn round_trips_8_bit_binary() {
+        assert_round_trips(&encode_ppm_binary(&test_image(), 255), 255);
+    }
+
+    #[test]
+    fn round_trips_16_bit_binary() {
+        let encoded = encode_ppm_binary(&test_image(), 65535);
+        assert_eq!(encoded.len(), "P6\n4 3\n65535\n".len() + (4 * 3 * 3 * 2));
+        assert_round_trips(&encoded, 65535);
+    }
+
+    fn decode_error(bytes: &[u8]) -> String {
+        decode_ppm(bytes).err().expect("should fail to decode")
+    }
+
+    #[test]
+    fn skips_comments_in_the_header() {
+        let decoded = decode_ppm(b"P3\n# made by hand\n1 1 # one pixel\n255\n255 0 51\n").unwrap();
+        assert_eq!(decoded.row(0)[0], Color::new(1.0, 0.0, 0.2));
+    }
+
+    #[test]
+    fn reports_malformed_headers() {
+        assert_eq!(decode_error(b"P5 1 1 255\n\0"), "unsupported PPM type `P5`, expected P3 or P6");
+        assert_eq!(decode_error(b"P6 wide 1 255\n"), "invalid width `wide`");
+        assert_eq!(decode_error(b"P6 1 1 0\n"), "maximum value 0 is out of range");
+        assert_eq!(decode_error(b"P6 1 1 65536\n"), "maximum value 65536 is out of range");
+        assert_eq!(decode_error(b"P6 1 1"), "file ends before all pixels were read");
+    }
+
+    #[test]
+    fn reports_missing_pixels_before_allocating() {
+        assert_eq!(
+            decode_error(b"P6 200000 200000 255\n\x01\x02\x03"),
+            "a 200000x200000 image needs at least 120000000000 bytes of pixels, but only 3 follow the header",
+        );
+        assert_eq!(
+            decode_error(b"P6 2 1 65535\n\0\0\0\0\0\0"),
+            "a 2x1 image needs at least 12 bytes of pixels, but only 6 follow the header",
+        );
+        assert_eq!(
+            decode_error(format!("P6 {} 2 255\n", usize::MAX).as_bytes()),
+            format!("image size {}x2 is too large", usize::MAX),
+        );
+    }
+
+    #[test]
+    fn reports_bad_plain_text_components() {
+        assert_eq!(decode_error(b"P3 1 1 255\n1 2 x\n"), "invalid pixel component `x`");
+        assert_eq!(decode_error(b"P3 2 1 255\n1 2 3 4 5\n"), "file ends before all pixels were read");
+    }
+}
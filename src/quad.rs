@@ -0,0 +1,106 @@
+use std::sync::Arc;
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::light::Light;
+use crate::material::Material;
+use crate::pos::Pos;
+use crate::ray::Ray;
+use crate::utils::rand_proportion;
+use crate::vec3::Vec3;
+
+/// A parallelogram, spanning `u` and `v` from `corner`.
+/// Its front face is the side that `u` x `v` points towards.
+pub struct Quad {
+    corner: Pos,
+    u: Vec3,
+    v: Vec3,
+    /// Unit normal of the quad's plane
+    normal: Vec3,
+    /// Distance of the plane from the origin, along `normal`
+    plane_distance: f64,
+    /// Converts points in the plane to coordinates along `u` and `v`
+    w: Vec3,
+    area: f64,
+    material: Arc<dyn Material>,
+    bounding_box: Aabb,
+}
+
+impl Quad {
+    pub fn new(
+        corner: Pos,
+        u: Vec3,
+        v: Vec3,
+        material: &Arc<dyn Material>,
+    ) -> Self {
+        let n = Vec3::cross(&u, &v);
+        let normal = n.unit_vector();
+        let bounding_box = Aabb::enclosing(
+            &Aabb::from_points(corner, corner + u + v),
+            &Aabb::from_points(corner + u, corner + v),
+        ).padded();
+        Self {
+            corner,
+            u,
+            v,
+            normal,
+            plane_distance: Vec3::dot(normal, *corner),
+            w: n / Vec3::dot(n, n),
+            area: n.length(),
+            material: Arc::clone(material),
+            bounding_box,
+        }
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, ray: Ray, ray_t: Interval) -> Option<HitRecord> {
+        let denominator = Vec3::dot(self.normal, ray.direction);
+        // The ray is parallel to the plane
+        if denominator.abs() < 1e-8 {
+            return None;
+        }
+        let t = (self.plane_distance - Vec3::dot(self.normal, *ray.origin)) / denominator;
+        if !ray_t.surrounds(t) {
+            return None;
+        }
+
+        // Find where in the parallelogram the ray hit the plane
+        let hit_pos = ray.at(t);
+        let planar = *hit_pos - *self.corner;
+        let alpha = Vec3::dot(self.w, Vec3::cross(&planar, &self.v));
+        let beta = Vec3::dot(self.w, Vec3::cross(&self.u, &planar));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        Some(HitRecord::new(t, hit_pos, ray, self.normal, &self.material).with_uv(alpha, beta))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+}
+
+impl Light for Quad {
+    /// Every point on the quad is equally likely
+    fn sample_direction(&self, origin: Pos) -> Option<Vec3> {
+        let point = self.corner + (rand_proportion() * self.u) + (rand_proportion() * self.v);
+        Some((*point - *origin).unit_vector())
+    }
+
+    fn pdf(&self, origin: Pos, direction: Vec3) -> f64 {
+        // With a unit direction, the hit's `t` is the distance to the quad
+        let direction = direction.unit_vector();
+        let Some(hit_record) = self.hit(Ray::new(origin, direction), Interval::new(0.001, f64::INFINITY)) else {
+            return 0.0;
+        };
+        // Convert the density over the quad's area to one over solid angle
+        let distance_squared = hit_record.t * hit_record.t;
+        let cosine = Vec3::dot(direction, self.normal).abs();
+        if cosine < 1e-8 {
+            return 0.0;
+        }
+        distance_squared / (cosine * self.area)
+    }
+}
@@ -0,0 +1,201 @@
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+
+/// Radiance RGBE (.hdr), with run-length encoded scanlines
+pub fn encode_radiance_hdr(framebuffer: &Framebuffer) -> Vec<u8> {
+    let width = framebuffer.width();
+    let mut out = vec![];
+    out.extend(format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n", framebuffer.height(), width).as_bytes());
+
+    for y in 0..framebuffer.height() {
+        let scanline: Vec<[u8; 4]> = framebuffer.row(y).iter().map(|pixel| to_rgbe(*pixel)).collect();
+        // Run-length encoding can only describe scanlines within this range
+        if !(8..=0x7fff).contains(&width) {
+            for rgbe in scanline.iter() {
+                out.extend(rgbe);
+            }
+            continue;
+        }
+
+        out.extend([2, 2, (width >> 8) as u8, (width & 0xff) as u8]);
+        // Each component is encoded separately
+        for component in 0..4 {
+            let bytes: Vec<u8> = scanline.iter().map(|rgbe| rgbe[component]).collect();
+            encode_run_lengths(&mut out, &bytes);
+        }
+    }
+    out
+}
+
+/// Shared-exponent encoding of a color
+fn to_rgbe(color: Color) -> [u8; 4] {
+    let max_component = color.r().max(color.g()).max(color.b());
+    if max_component.is_nan() || max_component <= 1e-32 {
+        return [0, 0, 0, 0];
+    }
+    // max_component = mantissa * 2^exponent, with the mantissa in [0.5, 1)
+    let exponent = max_component.log2().floor() as i32 + 1;
+    let scale = 256.0 / 2f64.powi(exponent);
+    let encode = |component: f64| (component.max(0.0) * scale).min(255.0) as u8;
+    [
+        encode(color.r()),
+        encode(color.g()),
+        encode(color.b()),
+        (exponent + 128).clamp(0, 255) as u8,
+    ]
+}
+
+/// Runs are only worth encoding once they're at least this long
+const MIN_RUN_LENGTH: usize = 4;
+
+fn encode_run_lengths(out: &mut Vec<u8>, bytes: &[u8]) {
+    let mut cursor = 0;
+    while cursor < bytes.len() {
+        // Find the next run that's long enough to be worth encoding
+        let mut run_start = cursor;
+        let mut run_length = 0;
+        while run_start < bytes.len() {
+            run_length = bytes[run_start..].iter()
+                .take(127)
+                .take_while(|b| **b == bytes[run_start])
+                .count();
+            if run_length >= MIN_RUN_LENGTH {
+                break;
+            }
+            run_start += run_length;
+        }
+
+        // Everything before the run is written out literally, in chunks of at most 128
+        while cursor < run_start {
+            let literal_length = usize::min(128, run_start - cursor);
+            out.push(literal_length as u8);
+            out.extend(&bytes[cursor..(cursor + literal_length)]);
+            cursor += literal_length;
+        }
+
+        if run_length >= MIN_RUN_LENGTH {
+            out.push(128 + run_length as u8);
+            out.push(bytes[run_start]);
+            cursor += run_length;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{rand_double, seed_rng};
+
+    fn from_rgbe(rgbe: [u8; 4]) -> Color {
+        if rgbe[3] == 0 {
+            return Color::black();
+        }
+        let scale = 2f64.powi(rgbe[3] as i32 - 128) / 256.0;
+        Color::new(rgbe[0] as f64 * scale, rgbe[1] as f64 * scale, rgbe[2] as f64 * scale)
+    }
+
+    fn decode_run_lengths(bytes: &[u8], cursor: &mut usize, count: usize) -> Vec<u8> {
+        let mut decoded = vec![];
+        while decoded.len() < count {
+            let code = bytes[*cursor] as usize;
+            *cursor += 1;
+            if code > 128 {
+                decoded.extend(std::iter::repeat_n(bytes[*cursor], code - 128));
+                *cursor += 1;
+            }
+            else {
+                assert!(code > 0, "literal runs can't be empty");
+                decoded.extend(&bytes[*cursor..*cursor + code]);
+                *cursor += code;
+            }
+        }
+        assert_eq!(decoded.len(), count, "a run overflowed the scanline");
+        decoded
+    }
+
+    fn decode_radiance_hdr(bytes: &[u8]) -> Framebuffer {
+        let header_end = bytes.windows(2).position(|pair| pair == b"\n\n").unwrap() + 2;
+        assert!(bytes.starts_with(b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n"));
+        let size_end = header_end + bytes[header_end..].iter().position(|byte| *byte == b'\n').unwrap();
+        let size = String::from_utf8_lossy(&bytes[header_end..size_end]).into_owned();
+        let words: Vec<&str> = size.split(' ').collect();
+        assert_eq!((words[0], words[2]), ("-Y", "+X"));
+        let (height, width): (usize, usize) = (words[1].parse().unwrap(), words[3].parse().unwrap());
+
+        let mut framebuffer = Framebuffer::new(width, height);
+        let mut cursor = size_end + 1;
+        for y in 0..height {
+            let scanline: Vec<[u8; 4]> = if bytes[cursor..].starts_with(&[2, 2]) {
+                assert_eq!(((bytes[cursor + 2] as usize) << 8) | bytes[cursor + 3] as usize, width);
+                cursor += 4;
+                let components: Vec<Vec<u8>> = (0..4).map(|_| decode_run_lengths(bytes, &mut cursor, width)).collect();
+                (0..width).map(|x| [components[0][x], components[1][x], components[2][x], components[3][x]]).collect()
+            }
+            else {
+                let scanline = bytes[cursor..cursor + (width * 4)].chunks_exact(4).map(|rgbe| rgbe.try_into().unwrap()).collect();
+                cursor += width * 4;
+                scanline
+            };
+            for (pixel, rgbe) in framebuffer.row_mut(y).iter_mut().zip(scanline) {
+                *pixel = from_rgbe(rgbe);
+            }
+        }
+        assert_eq!(cursor, bytes.len(), "unexpected data after the last scanline");
+        framebuffer
+    }
+
+    /// Each component survives to within the precision of its 8-bit mantissa
+    fn assert_round_trips(framebuffer: &Framebuffer) {
+        let decoded = decode_radiance_hdr(&encode_radiance_hdr(framebuffer));
+        assert_eq!((decoded.width(), decoded.height()), (framebuffer.width(), framebuffer.height()));
+        for y in 0..framebuffer.height() {
+            for (expected, actual) in framebuffer.row(y).iter().zip(decoded.row(y)) {
+                let tolerance = expected.r().max(expected.g()).max(expected.b()) / 128.0;
+                for component in 0..3 {
+                    assert!(
+                        (expected[component] - actual[component]).abs() <= tolerance,
+                        "expected {expected:?}, decoded {actual:?}",
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips_noisy_scanlines_as_literals() {
+        seed_rng(7);
+        let mut framebuffer = Framebuffer::new(300, 3);
+        for y in 0..3 {
+            for pixel in framebuffer.row_mut(y) {
+                *pixel = Color::new(rand_double(0.0, 50.0), rand_double(0.0, 1.0), rand_double(0.0, 0.01));
+            }
+        }
+        assert_round_trips(&framebuffer);
+    }
+
+    #[test]
+    fn round_trips_runs_longer_than_one_code() {
+        let mut framebuffer = Framebuffer::new(400, 2);
+        for (x, pixel) in framebuffer.row_mut(0).iter_mut().enumerate() {
+            // Long runs, broken up by short stretches of varying pixels
+            *pixel = if (x / 150) % 2 == 0 || x % 150 < 3 { Color::new(0.25, 0.5, 4.0) } else { Color::new(x as f64, 1.0, 0.0) };
+        }
+        assert_round_trips(&framebuffer);
+    }
+
+    #[test]
+    fn round_trips_narrow_images_without_run_length_encoding() {
+        let mut framebuffer = Framebuffer::new(3, 2);
+        framebuffer.row_mut(0).copy_from_slice(&[Color::black(), Color::white(), Color::new(1e6, 2.0, 0.5)]);
+        let encoded = encode_radiance_hdr(&framebuffer);
+        assert_eq!(encoded.len(), "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 2 +X 3\n".len() + (3 * 2 * 4));
+        assert_round_trips(&framebuffer);
+    }
+
+    #[test]
+    fn encodes_black_and_negative_colors_as_zero() {
+        assert_eq!(to_rgbe(Color::black()), [0, 0, 0, 0]);
+        assert_eq!(to_rgbe(Color::new(-1.0, -2.0, -3.0)), [0, 0, 0, 0]);
+        assert_eq!(to_rgbe(Color::new(f64::NAN, 0.0, 0.0)), [0, 0, 0, 0]);
+    }
+}
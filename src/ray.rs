@@ -5,23 +5,30 @@ use crate::vec3::Vec3;
 pub struct Ray {
     pub origin: Pos,
     pub direction: Vec3,
+    /// When the ray was cast, for scenes where things move during the exposure
+    pub time: f64,
 }
 
 impl Ray {
     pub fn new(
         origin: Pos,
         direction: Vec3,
+    ) -> Self {
+        Self::with_time(origin, direction, 0.0)
+    }
+
+    pub fn with_time(
+        origin: Pos,
+        direction: Vec3,
+        time: f64,
     ) -> Self {
         Self {
             origin,
             direction,
+            time,
         }
     }
 
-    pub fn zero() -> Self {
-        Self::new(Pos::zero(), Vec3::zero())
-    }
-
     pub fn origin(&self) -> Pos {
         self.origin
     }
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+use crate::ambient_occlusion::AmbientOcclusionIntegrator;
+use crate::camera::Camera;
+use crate::debug_integrator::DebugIntegrator;
+use crate::direct_lighting::DirectLightingIntegrator;
+use crate::integrator::{Integrator, IntegratorKind};
+use crate::material::Material;
+use crate::path_integrator::PathIntegrator;
+use crate::pos::Pos;
+use crate::vec3::Vec3;
+
+#[derive(Copy, Clone)]
+pub struct SceneParameters {
+    pub aspect_ratio: f64,
+    pub image_width: usize,
+    pub samples_per_pixel: usize,
+    pub max_ray_bounces: usize,
+    /// Bounces after which paths carrying little light may be ended early
+    pub russian_roulette_depth: usize,
+    pub integrator: IntegratorKind,
+    /// Rays cast from each surface point to find how occluded it is, by the ambient occlusion integrator
+    pub ambient_occlusion_samples: usize,
+    /// How far away geometry can be and still occlude a surface point, for the ambient occlusion integrator
+    pub ambient_occlusion_distance: f64,
+}
+
+/// Where the camera is, which way it faces, and how its lens is set up
+#[derive(Copy, Clone)]
+pub struct CameraSettings {
+    /// Camera position in the world
+    pub look_from: Pos,
+    /// Camera target/direction point
+    pub look_at: Pos,
+    /// Camera-relative up direction. Specifies camera rotation.
+    pub up: Vec3,
+    pub vertical_field_of_view_angle: f64,
+    /// Variation angle of rays through each pixel
+    pub defocus_angle: f64,
+    /// Distance from camera to plane of perfect focus
+    pub focus_distance: f64,
+}
+
+pub fn camera_with_params(
+    scene_params: SceneParameters,
+    background_material: Arc<dyn Material>,
+    settings: CameraSettings,
+) -> Camera {
+    Camera::new(
+        scene_params.aspect_ratio,
+        scene_params.image_width,
+        background_material,
+        settings.vertical_field_of_view_angle,
+        settings.look_from,
+        settings.look_at,
+        settings.up,
+        settings.defocus_angle,
+        settings.focus_distance,
+        scene_params.samples_per_pixel,
+        integrator_with_params(scene_params),
+    )
+}
+
+fn integrator_with_params(scene_params: SceneParameters) -> Box<dyn Integrator> {
+    match scene_params.integrator {
+        IntegratorKind::Path => Box::new(PathIntegrator::new(scene_params.max_ray_bounces, scene_params.russian_roulette_depth)),
+        IntegratorKind::Direct => Box::new(DirectLightingIntegrator::new(scene_params.max_ray_bounces)),
+        IntegratorKind::Debug => Box::new(DebugIntegrator),
+        IntegratorKind::AmbientOcclusion => Box::new(AmbientOcclusionIntegrator::new(
+            scene_params.ambient_occlusion_samples,
+            scene_params.ambient_occlusion_distance,
+        )),
+    }
+}
@@ -0,0 +1,1095 @@
+//! Scenes described in a JSON file, rather than in code.
+//!
+//! ```json
+//! {
+//!     "parameters": { "aspect_ratio": 1.7778, "image_width": 400, "samples_per_pixel": 100, "max_ray_bounces": 50 },
+//!     "camera": {
+//!         "look_from": [-2, 2, 1], "look_at": [0, 0, -1], "up": [0, 1, 0],
+//!         "vertical_field_of_view_angle": 20, "defocus_angle": 10, "focus_distance": 3.4,
+//!         "background": "sky"
+//!     },
+//!     "materials": {
+//!         "sky": { "type": "gradient", "from_color": [0.5, 0.7, 1.0], "to_color": [1, 1, 1] },
+//!         "ground": { "type": "lambertian", "albedo": [0.8, 0.8, 0.0] }
+//!     },
+//!     "objects": [
+//!         { "type": "sphere", "center": [0, -100.5, -1], "radius": 100, "material": "ground" }
+//!     ]
+//! }
+//! ```
+//!
+//! Materials are declared once by name, and shared by every object that refers to them.
+//! Textures work the same way: a lambertian or metal `albedo` is either a color,
+//! or the name of a texture declared in `textures`, such as
+//! `"checks": { "type": "checker", "mode": "uv", "scale": 0.1, "even": [1, 1, 1], "odd": "photo" }`.
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use serde::Deserialize;
+use crate::bvh::BvhNode;
+use crate::box_primitive::BoxPrimitive;
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::constant_medium::ConstantMedium;
+use crate::csg::{Csg, CsgOperation};
+use crate::cylinder::{Cone, Cylinder};
+use crate::dielectric::DielectricMaterial;
+use crate::disk::Disk;
+use crate::diffuse_light::DiffuseLightMaterial;
+use crate::gradient::GradientMaterial;
+use crate::henyey_greenstein::HenyeyGreensteinMaterial;
+use crate::heterogeneous_medium::HeterogeneousMedium;
+use crate::hittable::Hittable;
+use crate::hittable_list::HittableList;
+use crate::image_texture::{AddressMode, Filter, ImageTexture};
+use crate::integrator::IntegratorKind;
+use crate::light::LightList;
+use crate::isotropic::IsotropicMaterial;
+use crate::lambertian::LambertianMaterial;
+use crate::material::Material;
+use crate::matrix4::Matrix4;
+use crate::metal::MetalMaterial;
+use crate::moving_sphere::{Keyframe, MovingSphere};
+use crate::noise_texture::{MarbleTexture, NoiseTexture, WoodTexture};
+use crate::obj::load_obj;
+use crate::plane::Plane;
+use crate::pos::Pos;
+use crate::quad::Quad;
+use crate::scene::{camera_with_params, CameraSettings, SceneParameters};
+use crate::solid_color::SolidColorMaterial;
+use crate::sphere::Sphere;
+use crate::texture::{CheckerMode, CheckerTexture, SolidColor, Texture};
+use crate::transform::Transform;
+use crate::triangle::Triangle;
+use crate::triangle_mesh::{MeshData, MeshFace, TriangleMesh};
+use crate::vec3::Vec3;
+use crate::voxel_grid::VoxelGrid;
+
+#[derive(Debug)]
+pub enum SceneFileError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The file isn't valid JSON, or doesn't have the expected shape.
+    /// The underlying error knows the line and column.
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    /// The file parsed, but a field holds a value we can't use
+    InvalidField {
+        path: PathBuf,
+        field: String,
+        message: String,
+    },
+}
+
+impl Display for SceneFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SceneFileError::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            SceneFileError::Parse { path, source } => write!(f, "{}: {source}", path.display()),
+            SceneFileError::InvalidField { path, field, message } => write!(f, "{}: `{field}`: {message}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for SceneFileError {}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ParametersDescription {
+    aspect_ratio: Option<f64>,
+    image_width: Option<usize>,
+    samples_per_pixel: Option<usize>,
+    max_ray_bounces: Option<usize>,
+    russian_roulette_depth: Option<usize>,
+    integrator: Option<IntegratorKind>,
+    ambient_occlusion_samples: Option<usize>,
+    ambient_occlusion_distance: Option<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraDescription {
+    look_from: [f64; 3],
+    look_at: [f64; 3],
+    #[serde(default = "default_up")]
+    up: [f64; 3],
+    vertical_field_of_view_angle: f64,
+    #[serde(default)]
+    defocus_angle: f64,
+    focus_distance: f64,
+    /// When the shutter opens and closes, for motion blur
+    shutter: Option<[f64; 2]>,
+    /// Name of the material that colors rays which escape the scene
+    background: String,
+}
+
+fn default_up() -> [f64; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+/// Either a constant color, or the name of a declared texture
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TextureReference {
+    Color([f64; 3]),
+    Name(String),
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+enum CheckerModeDescription {
+    #[default]
+    Spatial,
+    Uv,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+enum AddressModeDescription {
+    #[default]
+    Wrap,
+    Clamp,
+    Mirror,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+enum FilterDescription {
+    Nearest,
+    #[default]
+    Bilinear,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CsgOperationDescription {
+    Union,
+    Intersection,
+    Difference,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum TextureDescription {
+    SolidColor {
+        color: [f64; 3],
+    },
+    Checker {
+        #[serde(default)]
+        mode: CheckerModeDescription,
+        scale: f64,
+        even: TextureReference,
+        odd: TextureReference,
+    },
+    /// A PNG or PPM image, found relative to the scene file
+    Image {
+        path: PathBuf,
+        #[serde(default)]
+        address_mode: AddressModeDescription,
+        #[serde(default)]
+        filter: FilterDescription,
+    },
+    /// Smooth gray Perlin noise. Textures with the same seed share the same noise.
+    Noise {
+        #[serde(default)]
+        seed: u64,
+        scale: f64,
+    },
+    Marble {
+        #[serde(default)]
+        seed: u64,
+        scale: f64,
+        base: [f64; 3],
+        veins: [f64; 3],
+    },
+    Wood {
+        #[serde(default)]
+        seed: u64,
+        /// Rings per unit of distance from the Y axis
+        scale: f64,
+        light: [f64; 3],
+        dark: [f64; 3],
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum MaterialDescription {
+    Lambertian {
+        albedo: TextureReference,
+    },
+    Metal {
+        albedo: TextureReference,
+        #[serde(default)]
+        fuzz: f64,
+    },
+    Dielectric {
+        index_of_refraction: f64,
+    },
+    DiffuseLight {
+        emit: [f64; 3],
+    },
+    /// Scatters equally in every direction, for the inside of a `constant_medium`
+    Isotropic {
+        albedo: TextureReference,
+    },
+    /// Scatters mostly forwards with a positive `asymmetry`, or backwards with a negative one, for clouds and smoke.
+    /// An `emit` color makes the medium glow, like fire.
+    HenyeyGreenstein {
+        albedo: TextureReference,
+        #[serde(default)]
+        asymmetry: f64,
+        #[serde(default)]
+        emit: [f64; 3],
+    },
+    Gradient {
+        from_color: [f64; 3],
+        to_color: [f64; 3],
+    },
+    SolidColor {
+        color: [f64; 3],
+    },
+}
+
+impl MaterialDescription {
+    /// Whether this material can color rays that miss everything
+    fn is_background(&self) -> bool {
+        matches!(self, MaterialDescription::Gradient { .. } | MaterialDescription::SolidColor { .. })
+    }
+
+    fn is_emissive(&self) -> bool {
+        matches!(self, MaterialDescription::DiffuseLight { .. })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum ObjectDescription {
+    Sphere {
+        center: [f64; 3],
+        radius: f64,
+        material: String,
+    },
+    /// An infinite plane, facing the way `normal` points
+    Plane {
+        point: [f64; 3],
+        normal: [f64; 3],
+        material: String,
+    },
+    /// A parallelogram spanning the edges `u` and `v` from `corner`, facing the way `u` x `v` points
+    Quad {
+        corner: [f64; 3],
+        u: [f64; 3],
+        v: [f64; 3],
+        material: String,
+    },
+    Disk {
+        center: [f64; 3],
+        normal: [f64; 3],
+        radius: f64,
+        material: String,
+    },
+    /// An axis-aligned box between two opposite corners.
+    /// `faces` can give some faces their own material, instead of `material`.
+    Box {
+        min: [f64; 3],
+        max: [f64; 3],
+        material: String,
+        #[serde(default)]
+        faces: BoxFacesDescription,
+    },
+    Cylinder {
+        base: [f64; 3],
+        top: [f64; 3],
+        radius: f64,
+        #[serde(default)]
+        capped: bool,
+        material: String,
+    },
+    /// A cone from the center of its base to its tip, or with a `top_radius`, a truncated cone
+    Cone {
+        base: [f64; 3],
+        top: [f64; 3],
+        base_radius: f64,
+        #[serde(default)]
+        top_radius: f64,
+        #[serde(default)]
+        capped: bool,
+        material: String,
+    },
+    /// A sphere moving in straight lines between the centers given at each keyframe
+    MovingSphere {
+        keyframes: Vec<KeyframeDescription>,
+        radius: f64,
+        material: String,
+    },
+    /// Fog or smoke of uniform `density` filling a closed `boundary` object,
+    /// usually with an isotropic material
+    ConstantMedium {
+        boundary: Box<ObjectDescription>,
+        density: f64,
+        material: String,
+    },
+    /// Two closed objects combined into one solid: a `union` of both, their `intersection`,
+    /// or the `difference` left after carving `right` out of `left`
+    Csg {
+        operation: CsgOperationDescription,
+        left: Box<ObjectDescription>,
+        right: Box<ObjectDescription>,
+    },
+    /// A medium whose density follows a dense voxel grid file, found relative to the scene file,
+    /// stretched to fill the box from `min` to `max`. The grid's values are multiplied by `density`.
+    HeterogeneousMedium {
+        path: PathBuf,
+        min: [f64; 3],
+        max: [f64; 3],
+        #[serde(default = "default_density")]
+        density: f64,
+        material: String,
+    },
+    /// Vertices are given counter-clockwise, seen from the front
+    Triangle {
+        vertices: [[f64; 3]; 3],
+        normals: Option<[[f64; 3]; 3]>,
+        uvs: Option<[[f64; 2]; 3]>,
+        material: String,
+    },
+    /// A Wavefront OBJ model, found relative to the scene file.
+    /// `material` is used for faces that don't pick one from the model's MTL library,
+    /// and `groups` limits the model to just the named groups.
+    Obj {
+        path: PathBuf,
+        material: Option<String>,
+        groups: Option<Vec<String>>,
+    },
+    /// Triangles indexing into a shared list of positions
+    Mesh {
+        positions: Vec<[f64; 3]>,
+        faces: Vec<[usize; 3]>,
+        material: String,
+    },
+    /// A copy of an assembly, moved into place by each transformation step in turn.
+    /// Every instance shares the assembly's geometry.
+    Instance {
+        assembly: String,
+        #[serde(default)]
+        transform: Vec<TransformStep>,
+    },
+}
+
+fn default_density() -> f64 {
+    1.0
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct BoxFacesDescription {
+    left: Option<String>,
+    right: Option<String>,
+    bottom: Option<String>,
+    top: Option<String>,
+    back: Option<String>,
+    front: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum TransformStep {
+    Translate([f64; 3]),
+    /// Counter-clockwise by `angle` degrees, looking down `axis` towards the origin
+    Rotate {
+        axis: [f64; 3],
+        angle: f64,
+    },
+    Scale([f64; 3]),
+}
+
+/// Everything built so far that objects can refer to
+struct ObjectContext<'a> {
+    materials: BTreeMap<&'a str, Arc<dyn Material>>,
+    assemblies: BTreeMap<&'a str, Arc<dyn Hittable>>,
+    /// Assemblies currently being built, to catch assemblies that contain themselves
+    in_progress: Vec<&'a str>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeyframeDescription {
+    time: f64,
+    center: [f64; 3],
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneDescription {
+    parameters: Option<ParametersDescription>,
+    camera: CameraDescription,
+    #[serde(default)]
+    textures: BTreeMap<String, TextureDescription>,
+    #[serde(default)]
+    materials: BTreeMap<String, MaterialDescription>,
+    #[serde(default)]
+    objects: Vec<ObjectDescription>,
+    /// Named lists of objects, placed in the scene by `instance` objects
+    #[serde(default)]
+    assemblies: BTreeMap<String, Vec<ObjectDescription>>,
+}
+
+/// A parsed scene file, ready to be turned into a world and a camera
+pub struct SceneFile {
+    path: PathBuf,
+    description: SceneDescription,
+}
+
+impl SceneFile {
+    pub fn open(path: &Path) -> Result<Self, SceneFileError> {
+        let text = fs::read_to_string(path).map_err(|source| SceneFileError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(path, &text)
+    }
+
+    /// `path` is only used to describe errors
+    pub fn parse(path: &Path, text: &str) -> Result<Self, SceneFileError> {
+        let description = serde_json::from_str(text).map_err(|source| SceneFileError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            description,
+        })
+    }
+
+    /// The render parameters given in the file, with anything unspecified taken from `defaults`
+    pub fn parameters(&self, defaults: SceneParameters) -> SceneParameters {
+        let Some(params) = &self.description.parameters else {
+            return defaults;
+        };
+        SceneParameters {
+            aspect_ratio: params.aspect_ratio.unwrap_or(defaults.aspect_ratio),
+            image_width: params.image_width.unwrap_or(defaults.image_width),
+            samples_per_pixel: params.samples_per_pixel.unwrap_or(defaults.samples_per_pixel),
+            max_ray_bounces: params.max_ray_bounces.unwrap_or(defaults.max_ray_bounces),
+            russian_roulette_depth: params.russian_roulette_depth.unwrap_or(defaults.russian_roulette_depth),
+            integrator: params.integrator.unwrap_or(defaults.integrator),
+            ambient_occlusion_samples: params.ambient_occlusion_samples.unwrap_or(defaults.ambient_occlusion_samples),
+            ambient_occlusion_distance: params.ambient_occlusion_distance.unwrap_or(defaults.ambient_occlusion_distance),
+        }
+    }
+
+    pub fn build(&self, scene_params: SceneParameters) -> Result<(HittableList, Camera), SceneFileError> {
+        self.validate_parameters(scene_params)?;
+
+        let mut textures = BTreeMap::new();
+        for name in self.description.textures.keys() {
+            self.build_texture(&mut textures, &mut vec![], name)?;
+        }
+
+        let mut materials = BTreeMap::new();
+        for (name, description) in self.description.materials.iter() {
+            materials.insert(name.as_str(), self.build_material(&textures, name, description)?);
+        }
+
+        let mut context = ObjectContext {
+            materials,
+            assemblies: BTreeMap::new(),
+            in_progress: vec![],
+        };
+        // Build every assembly, even unused ones, so mistakes in them are still reported
+        for name in self.description.assemblies.keys() {
+            self.build_assembly(&mut context, name)?;
+        }
+        let world = self.build_objects(&mut context, "objects", &self.description.objects)?;
+
+        let camera = &self.description.camera;
+        let background_name = &camera.background;
+        match self.description.materials.get(background_name) {
+            Some(description) if !description.is_background() => {
+                return Err(self.invalid_field(
+                    "camera.background",
+                    format!("material \"{background_name}\" can't be used as a background, only gradient and solid_color materials can"),
+                ));
+            }
+            _ => {}
+        }
+        let background = self.lookup_material(&context.materials, "camera.background", background_name)?;
+
+        let mut scene_camera = camera_with_params(
+            scene_params,
+            Arc::clone(background),
+            CameraSettings {
+                look_from: pos(camera.look_from),
+                look_at: pos(camera.look_at),
+                up: vec3(camera.up),
+                vertical_field_of_view_angle: camera.vertical_field_of_view_angle,
+                defocus_angle: camera.defocus_angle,
+                focus_distance: camera.focus_distance,
+            },
+        );
+        if let Some([open, close]) = camera.shutter {
+            if close < open {
+                return Err(self.invalid_field("camera.shutter", "must close after it opens".to_string()));
+            }
+            scene_camera.set_shutter(open, close);
+        }
+        scene_camera.set_lights(self.build_lights(&context.materials));
+        Ok((world, scene_camera))
+    }
+
+    /// Emissive spheres and quads among the top-level objects, which rays can be aimed at directly.
+    /// Other emitters, and those inside assemblies, are still lit up by the rays that happen to hit them.
+    fn build_lights(&self, materials: &BTreeMap<&str, Arc<dyn Material>>) -> LightList {
+        let is_emissive = |name: &String| self.description.materials.get(name).is_some_and(MaterialDescription::is_emissive);
+        let mut lights = LightList::new();
+        for object in self.description.objects.iter() {
+            match object {
+                ObjectDescription::Sphere { center, radius, material } if is_emissive(material) => {
+                    lights.add(Box::new(Sphere::new(pos(*center), *radius, &materials[material.as_str()])));
+                }
+                ObjectDescription::Quad { corner, u, v, material } if is_emissive(material) => {
+                    lights.add(Box::new(Quad::new(pos(*corner), vec3(*u), vec3(*v), &materials[material.as_str()])));
+                }
+                _ => {}
+            }
+        }
+        lights
+    }
+
+    fn build_objects<'a>(
+        &'a self,
+        context: &mut ObjectContext<'a>,
+        field: &str,
+        objects: &'a [ObjectDescription],
+    ) -> Result<HittableList, SceneFileError> {
+        let mut list = HittableList::new();
+        for (i, object) in objects.iter().enumerate() {
+            self.build_object(context, &format!("{field}[{i}]"), object, &mut list)?;
+        }
+        Ok(list)
+    }
+
+    /// Build an object nested inside another, such as a medium's boundary
+    fn build_nested_object<'a>(
+        &'a self,
+        context: &mut ObjectContext<'a>,
+        field: &str,
+        object: &'a ObjectDescription,
+    ) -> Result<Box<dyn Hittable>, SceneFileError> {
+        let mut list = HittableList::new();
+        self.build_object(context, field, object, &mut list)?;
+        // Most descriptions are a single object, which is better used directly than through a list
+        let mut objects = list.into_objects();
+        Ok(if objects.len() == 1 {
+            objects.remove(0)
+        }
+        else {
+            let mut list = HittableList::new();
+            for object in objects {
+                list.add(object);
+            }
+            Box::new(list)
+        })
+    }
+
+    /// Add the objects built from a description to `list`. A model can contribute more than one.
+    fn build_object<'a>(
+        &'a self,
+        context: &mut ObjectContext<'a>,
+        field: &str,
+        object: &'a ObjectDescription,
+        list: &mut HittableList,
+    ) -> Result<(), SceneFileError> {
+        match object {
+            ObjectDescription::Sphere { center, radius, material } => {
+                let material = self.lookup_material(&context.materials, &format!("{field}.material"), material)?;
+                list.add(Box::new(Sphere::new(pos(*center), *radius, material)));
+            }
+            ObjectDescription::Plane { point, normal, material } => {
+                let normal = self.direction(&format!("{field}.normal"), *normal)?;
+                let material = self.lookup_material(&context.materials, &format!("{field}.material"), material)?;
+                list.add(Box::new(Plane::new(pos(*point), normal, material)));
+            }
+            ObjectDescription::Quad { corner, u, v, material } => {
+                if Vec3::cross(&vec3(*u), &vec3(*v)).is_near_zero() {
+                    return Err(self.invalid_field(field, "`u` and `v` must not be parallel".to_string()));
+                }
+                let material = self.lookup_material(&context.materials, &format!("{field}.material"), material)?;
+                list.add(Box::new(Quad::new(pos(*corner), vec3(*u), vec3(*v), material)));
+            }
+            ObjectDescription::Disk { center, normal, radius, material } => {
+                let normal = self.direction(&format!("{field}.normal"), *normal)?;
+                let material = self.lookup_material(&context.materials, &format!("{field}.material"), material)?;
+                list.add(Box::new(Disk::new(pos(*center), normal, *radius, material)));
+            }
+            ObjectDescription::Box { min, max, material, faces } => {
+                let material = self.lookup_material(&context.materials, &format!("{field}.material"), material)?;
+                let face_material = |face: &str, name: &Option<String>| match name {
+                    Some(name) => self.lookup_material(&context.materials, &format!("{field}.faces.{face}"), name).map(Arc::clone),
+                    None => Ok(Arc::clone(material)),
+                };
+                let faces = [
+                    face_material("left", &faces.left)?,
+                    face_material("right", &faces.right)?,
+                    face_material("bottom", &faces.bottom)?,
+                    face_material("top", &faces.top)?,
+                    face_material("back", &faces.back)?,
+                    face_material("front", &faces.front)?,
+                ];
+                list.add(Box::new(BoxPrimitive::with_face_materials(pos(*min), pos(*max), faces)));
+            }
+            ObjectDescription::Cylinder { base, top, radius, capped, material } => {
+                if base == top {
+                    return Err(self.invalid_field(&format!("{field}.top"), "must differ from `base`".to_string()));
+                }
+                let material = self.lookup_material(&context.materials, &format!("{field}.material"), material)?;
+                list.add(Box::new(Cylinder::new(pos(*base), pos(*top), *radius, *capped, material)));
+            }
+            ObjectDescription::Cone { base, top, base_radius, top_radius, capped, material } => {
+                if base == top {
+                    return Err(self.invalid_field(&format!("{field}.top"), "must differ from `base`".to_string()));
+                }
+                let material = self.lookup_material(&context.materials, &format!("{field}.material"), material)?;
+                list.add(Box::new(Cone::new(pos(*base), pos(*top), *base_radius, *top_radius, *capped, material)));
+            }
+            ObjectDescription::MovingSphere { keyframes, radius, material } => {
+                if keyframes.is_empty() {
+                    return Err(self.invalid_field(&format!("{field}.keyframes"), "must have at least one keyframe".to_string()));
+                }
+                let material = self.lookup_material(&context.materials, &format!("{field}.material"), material)?;
+                let keyframes = keyframes.iter()
+                    .map(|keyframe| Keyframe { time: keyframe.time, center: pos(keyframe.center) })
+                    .collect();
+                list.add(Box::new(MovingSphere::new(keyframes, *radius, material)));
+            }
+            ObjectDescription::ConstantMedium { boundary, density, material } => {
+                if *density <= 0.0 {
+                    return Err(self.invalid_field(&format!("{field}.density"), "must be positive".to_string()));
+                }
+                let boundary = self.build_nested_object(context, &format!("{field}.boundary"), boundary)?;
+                let material = self.lookup_material(&context.materials, &format!("{field}.material"), material)?;
+                list.add(Box::new(ConstantMedium::new(boundary, *density, material)));
+            }
+            ObjectDescription::Csg { operation, left, right } => {
+                let operation = match operation {
+                    CsgOperationDescription::Union => CsgOperation::Union,
+                    CsgOperationDescription::Intersection => CsgOperation::Intersection,
+                    CsgOperationDescription::Difference => CsgOperation::Difference,
+                };
+                let left = self.build_nested_object(context, &format!("{field}.left"), left)?;
+                let right = self.build_nested_object(context, &format!("{field}.right"), right)?;
+                list.add(Box::new(Csg::new(operation, left, right)));
+            }
+            ObjectDescription::HeterogeneousMedium { path, min, max, density, material } => {
+                if *density <= 0.0 {
+                    return Err(self.invalid_field(&format!("{field}.density"), "must be positive".to_string()));
+                }
+                if (0..3).any(|axis| min[axis] >= max[axis]) {
+                    return Err(self.invalid_field(&format!("{field}.max"), "must be above `min` along every axis".to_string()));
+                }
+                let material = self.lookup_material(&context.materials, &format!("{field}.material"), material)?;
+                let grid_path = self.path.parent().unwrap_or(Path::new("")).join(path);
+                let grid = VoxelGrid::load(&grid_path)
+                    .map_err(|e| self.invalid_field(&format!("{field}.path"), e.to_string()))?;
+                list.add(Box::new(HeterogeneousMedium::new(grid, pos(*min), pos(*max), *density, material)));
+            }
+            ObjectDescription::Triangle { vertices, normals, uvs, material } => {
+                let material = self.lookup_material(&context.materials, &format!("{field}.material"), material)?;
+                let mut triangle = Triangle::new(vertices.map(pos), material);
+                if let Some(normals) = normals {
+                    triangle = triangle.with_normals(normals.map(|n| vec3(n).unit_vector()));
+                }
+                if let Some(uvs) = uvs {
+                    triangle = triangle.with_uvs(uvs.map(|uv| (uv[0], uv[1])));
+                }
+                list.add(Box::new(triangle));
+            }
+            ObjectDescription::Obj { path, material, groups } => {
+                let default_material = match material {
+                    Some(material) => Arc::clone(self.lookup_material(&context.materials, &format!("{field}.material"), material)?),
+                    None => LambertianMaterial::new(Color::new(0.5, 0.5, 0.5)) as Arc<dyn Material>,
+                };
+                let model_path = self.path.parent().unwrap_or(Path::new("")).join(path);
+                let meshes = load_obj(&model_path, &default_material)
+                    .map_err(|e| self.invalid_field(&format!("{field}.path"), e.to_string()))?;
+                if let Some(groups) = groups {
+                    if let Some(missing) = groups.iter().find(|group| !meshes.iter().any(|mesh| &mesh.group == *group)) {
+                        return Err(self.invalid_field(&format!("{field}.groups"), format!("the model has no group named \"{missing}\"")));
+                    }
+                }
+                for obj_mesh in meshes {
+                    if groups.as_ref().is_none_or(|groups| groups.contains(&obj_mesh.group)) {
+                        list.add(Box::new(obj_mesh.mesh));
+                    }
+                }
+            }
+            ObjectDescription::Instance { assembly, transform } => {
+                if !self.description.assemblies.contains_key(assembly) {
+                    return Err(self.invalid_field(
+                        &format!("{field}.assembly"),
+                        format!("no assembly named \"{assembly}\" is declared in `assemblies`"),
+                    ));
+                }
+                let object = self.build_assembly(context, assembly)?;
+                // Each step applies after the ones before it
+                let object_to_world = transform.iter().fold(Matrix4::identity(), |matrix, step| {
+                    let step = match step {
+                        TransformStep::Translate(offset) => Matrix4::translation(vec3(*offset)),
+                        TransformStep::Rotate { axis, angle } => Matrix4::rotation(vec3(*axis), *angle),
+                        TransformStep::Scale(factors) => Matrix4::scale(vec3(*factors)),
+                    };
+                    step * matrix
+                });
+                let instance = Transform::new(object, object_to_world).ok_or_else(|| {
+                    self.invalid_field(&format!("{field}.transform"), "flattens the assembly, so can't be undone".to_string())
+                })?;
+                list.add(Box::new(instance));
+            }
+            ObjectDescription::Mesh { positions, faces, material } => {
+                let material = self.lookup_material(&context.materials, &format!("{field}.material"), material)?;
+                let mesh = TriangleMesh::new(MeshData {
+                    positions: positions.iter().map(|p| pos(*p)).collect(),
+                    normals: vec![],
+                    uvs: vec![],
+                    faces: faces.iter().map(|positions| MeshFace {
+                        positions: *positions,
+                        normals: None,
+                        uvs: None,
+                    }).collect(),
+                    material: Arc::clone(material),
+                }).map_err(|e| self.invalid_field(&format!("{field}.faces"), e.to_string()))?;
+                list.add(Box::new(mesh));
+            }
+        }
+        Ok(())
+    }
+
+    /// Build the named assembly once, sharing it between every instance
+    fn build_assembly<'a>(
+        &'a self,
+        context: &mut ObjectContext<'a>,
+        name: &'a str,
+    ) -> Result<Arc<dyn Hittable>, SceneFileError> {
+        if let Some(assembly) = context.assemblies.get(name) {
+            return Ok(Arc::clone(assembly));
+        }
+        let field = format!("assemblies.{name}");
+        if context.in_progress.contains(&name) {
+            return Err(self.invalid_field(&field, "assembly contains an instance of itself".to_string()));
+        }
+        let objects = &self.description.assemblies[name];
+        if objects.is_empty() {
+            return Err(self.invalid_field(&field, "must contain at least one object".to_string()));
+        }
+
+        context.in_progress.push(name);
+        let list = self.build_objects(context, &field, objects)?;
+        context.in_progress.pop();
+
+        let assembly: Arc<dyn Hittable> = Arc::new(BvhNode::new(list));
+        context.assemblies.insert(name, Arc::clone(&assembly));
+        Ok(assembly)
+    }
+
+    fn validate_parameters(&self, scene_params: SceneParameters) -> Result<(), SceneFileError> {
+        if scene_params.aspect_ratio <= 0.0 {
+            return Err(self.invalid_field("parameters.aspect_ratio", "must be positive".to_string()));
+        }
+        if scene_params.image_width == 0 {
+            return Err(self.invalid_field("parameters.image_width", "must be at least 1".to_string()));
+        }
+        if scene_params.samples_per_pixel == 0 {
+            return Err(self.invalid_field("parameters.samples_per_pixel", "must be at least 1".to_string()));
+        }
+        if scene_params.ambient_occlusion_samples == 0 {
+            return Err(self.invalid_field("parameters.ambient_occlusion_samples", "must be at least 1".to_string()));
+        }
+        if scene_params.ambient_occlusion_distance.is_nan() || scene_params.ambient_occlusion_distance <= 0.0 {
+            return Err(self.invalid_field("parameters.ambient_occlusion_distance", "must be positive".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Build the named texture into `textures`, first building any textures it refers to.
+    /// `in_progress` holds the textures currently being built, to catch textures that refer to themselves.
+    fn build_texture<'a>(
+        &'a self,
+        textures: &mut BTreeMap<&'a str, Arc<dyn Texture>>,
+        in_progress: &mut Vec<&'a str>,
+        name: &'a str,
+    ) -> Result<Arc<dyn Texture>, SceneFileError> {
+        if let Some(texture) = textures.get(name) {
+            return Ok(Arc::clone(texture));
+        }
+        let field = format!("textures.{name}");
+        if in_progress.contains(&name) {
+            return Err(self.invalid_field(&field, "texture refers to itself".to_string()));
+        }
+        in_progress.push(name);
+
+        let texture: Arc<dyn Texture> = match &self.description.textures[name] {
+            TextureDescription::SolidColor { color: c } => SolidColor::new(color(*c)),
+            TextureDescription::Checker { mode, scale, even, odd } => {
+                if *scale <= 0.0 {
+                    return Err(self.invalid_field(&format!("{field}.scale"), "must be positive".to_string()));
+                }
+                let mut resolve = |reference: &'a TextureReference, side: &str| match reference {
+                    TextureReference::Color(c) => Ok(SolidColor::new(color(*c)) as Arc<dyn Texture>),
+                    TextureReference::Name(other) if self.description.textures.contains_key(other) => {
+                        self.build_texture(textures, in_progress, other)
+                    }
+                    TextureReference::Name(other) => Err(self.invalid_field(
+                        &format!("{field}.{side}"),
+                        format!("no texture named \"{other}\" is declared in `textures`"),
+                    )),
+                };
+                let even = resolve(even, "even")?;
+                let odd = resolve(odd, "odd")?;
+                let mode = match mode {
+                    CheckerModeDescription::Spatial => CheckerMode::Spatial,
+                    CheckerModeDescription::Uv => CheckerMode::Uv,
+                };
+                CheckerTexture::new(mode, *scale, even, odd)
+            }
+            TextureDescription::Image { path, address_mode, filter } => {
+                let image_path = self.path.parent().unwrap_or(Path::new("")).join(path);
+                let address_mode = match address_mode {
+                    AddressModeDescription::Wrap => AddressMode::Wrap,
+                    AddressModeDescription::Clamp => AddressMode::Clamp,
+                    AddressModeDescription::Mirror => AddressMode::Mirror,
+                };
+                let filter = match filter {
+                    FilterDescription::Nearest => Filter::Nearest,
+                    FilterDescription::Bilinear => Filter::Bilinear,
+                };
+                ImageTexture::load(&image_path, address_mode, filter).map_err(|e| self.invalid_field(&format!("{field}.path"), e.to_string()))?
+            }
+            TextureDescription::Noise { seed, scale } => NoiseTexture::new(*seed, *scale),
+            TextureDescription::Marble { seed, scale, base, veins } => MarbleTexture::new(*seed, *scale, color(*base), color(*veins)),
+            TextureDescription::Wood { seed, scale, light, dark } => WoodTexture::new(*seed, *scale, color(*light), color(*dark)),
+        };
+
+        in_progress.pop();
+        textures.insert(name, Arc::clone(&texture));
+        Ok(texture)
+    }
+
+    fn lookup_texture(
+        &self,
+        textures: &BTreeMap<&str, Arc<dyn Texture>>,
+        field: &str,
+        reference: &TextureReference,
+    ) -> Result<Arc<dyn Texture>, SceneFileError> {
+        match reference {
+            TextureReference::Color(c) => Ok(SolidColor::new(color(*c))),
+            TextureReference::Name(name) => textures.get(name.as_str()).cloned().ok_or_else(|| {
+                self.invalid_field(field, format!("no texture named \"{name}\" is declared in `textures`"))
+            }),
+        }
+    }
+
+    fn build_material(
+        &self,
+        textures: &BTreeMap<&str, Arc<dyn Texture>>,
+        name: &str,
+        description: &MaterialDescription,
+    ) -> Result<Arc<dyn Material>, SceneFileError> {
+        let albedo_field = format!("materials.{name}.albedo");
+        Ok(match description {
+            MaterialDescription::Lambertian { albedo } => {
+                LambertianMaterial::with_texture(self.lookup_texture(textures, &albedo_field, albedo)?)
+            }
+            MaterialDescription::Metal { albedo, fuzz } => {
+                MetalMaterial::with_texture(self.lookup_texture(textures, &albedo_field, albedo)?, *fuzz)
+            }
+            MaterialDescription::Dielectric { index_of_refraction } => {
+                if *index_of_refraction <= 0.0 {
+                    return Err(self.invalid_field(
+                        &format!("materials.{name}.index_of_refraction"),
+                        "must be positive".to_string(),
+                    ));
+                }
+                DielectricMaterial::new(*index_of_refraction)
+            }
+            MaterialDescription::DiffuseLight { emit } => DiffuseLightMaterial::new(color(*emit)),
+            MaterialDescription::Isotropic { albedo } => {
+                IsotropicMaterial::with_texture(self.lookup_texture(textures, &albedo_field, albedo)?)
+            }
+            MaterialDescription::HenyeyGreenstein { albedo, asymmetry, emit } => {
+                if !(-1.0 < *asymmetry && *asymmetry < 1.0) {
+                    return Err(self.invalid_field(
+                        &format!("materials.{name}.asymmetry"),
+                        "must be between -1 and 1".to_string(),
+                    ));
+                }
+                HenyeyGreensteinMaterial::new(self.lookup_texture(textures, &albedo_field, albedo)?, *asymmetry, color(*emit))
+            }
+            MaterialDescription::Gradient { from_color, to_color } => GradientMaterial::new(color(*from_color), color(*to_color)),
+            MaterialDescription::SolidColor { color: c } => SolidColorMaterial::new(color(*c)),
+        })
+    }
+
+    fn lookup_material<'a>(
+        &self,
+        materials: &'a BTreeMap<&str, Arc<dyn Material>>,
+        field: &str,
+        name: &str,
+    ) -> Result<&'a Arc<dyn Material>, SceneFileError> {
+        materials.get(name).ok_or_else(|| {
+            self.invalid_field(field, format!("no material named \"{name}\" is declared in `materials`"))
+        })
+    }
+
+    /// A direction, which can't be the zero vector
+    fn direction(&self, field: &str, v: [f64; 3]) -> Result<Vec3, SceneFileError> {
+        let direction = vec3(v);
+        if direction.is_near_zero() {
+            return Err(self.invalid_field(field, "must not be zero".to_string()));
+        }
+        Ok(direction)
+    }
+
+    fn invalid_field(&self, field: &str, message: String) -> SceneFileError {
+        SceneFileError::InvalidField {
+            path: self.path.clone(),
+            field: field.to_string(),
+            message,
+        }
+    }
+}
+
+fn pos(v: [f64; 3]) -> Pos {
+    Pos::new(v[0], v[1], v[2])
+}
+
+fn vec3(v: [f64; 3]) -> Vec3 {
+    Vec3::new(v[0], v[1], v[2])
+}
+
+fn color(v: [f64; 3]) -> Color {
+    Color::new(v[0], v[1], v[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrator::IntegratorKind;
+
+    fn test_parameters() -> SceneParameters {
+        SceneParameters {
+            aspect_ratio: 1.0,
+            image_width: 8,
+            samples_per_pixel: 1,
+            max_ray_bounces: 4,
+            russian_roulette_depth: 2,
+            integrator: IntegratorKind::Path,
+            ambient_occlusion_samples: 1,
+            ambient_occlusion_distance: 1.0,
+        }
+    }
+
+    /// A scene with the given materials and objects, seen by a camera with a gradient background
+    fn scene_text(materials: &str, objects: &str) -> String {
+        format!(r#"{{
+            "camera": {{
+                "look_from": [0, 0, 1], "look_at": [0, 0, 0],
+                "vertical_field_of_view_angle": 40, "focus_distance": 1, "background": "sky"
+            }},
+            "materials": {{
+                "sky": {{ "type": "gradient", "from_color": [0.5, 0.7, 1.0], "to_color": [1, 1, 1] }},
+                "grey": {{ "type": "lambertian", "albedo": [0.5, 0.5, 0.5] }}
+                {materials}
+            }},
+            "objects": [{objects}]
+        }}"#)
+    }
+
+    fn build_error(text: &str) -> String {
+        let scene_file = SceneFile::parse(Path::new("scene.json"), text).expect("scene should parse");
+        scene_file.build(test_parameters()).err().expect("scene should fail to build").to_string()
+    }
+
+    #[test]
+    fn builds_a_valid_scene() {
+        let text = scene_text("", r#"{ "type": "sphere", "center": [0, 0, -1], "radius": 0.5, "material": "grey" }"#);
+        let scene_file = SceneFile::parse(Path::new("scene.json"), &text).unwrap();
+        assert!(scene_file.build(test_parameters()).is_ok());
+    }
+
+    #[test]
+    fn reports_syntax_errors_with_the_file_path() {
+        let error = SceneFile::parse(Path::new("scene.json"), "{ \"camera\": ").err().unwrap().to_string();
+        assert!(error.starts_with("scene.json: EOF while parsing"), "{error}");
+    }
+
+    #[test]
+    fn reports_missing_files() {
+        let error = SceneFile::open(Path::new("no/such/scene.json")).err().unwrap().to_string();
+        assert!(error.starts_with("no/such/scene.json: "), "{error}");
+    }
+
+    #[test]
+    fn reports_unknown_materials_by_field() {
+        let text = scene_text("", r#"
+            { "type": "sphere", "center": [0, 0, -1], "radius": 0.5, "material": "grey" },
+            { "type": "sphere", "center": [0, 0, -1], "radius": 0.5, "material": "gold" }
+        "#);
+        assert_eq!(
+            build_error(&text),
+            "scene.json: `objects[1].material`: no material named \"gold\" is declared in `materials`",
+        );
+    }
+
+    #[test]
+    fn reports_nested_fields_by_their_full_path() {
+        let text = scene_text("", r#"{
+            "type": "csg", "operation": "difference",
+            "left": { "type": "sphere", "center": [0, 0, -1], "radius": 0.5, "material": "grey" },
+            "right": {
+                "type": "csg", "operation": "union",
+                "left": { "type": "sphere", "center": [0, 0, -1], "radius": 0.2, "material": "grey" },
+                "right": { "type": "sphere", "center": [0, 0, -1], "radius": 0.2, "material": "gold" }
+            }
+        }"#);
+        assert_eq!(
+            build_error(&text),
+            "scene.json: `objects[0].right.right.material`: no material named \"gold\" is declared in `materials`",
+        );
+    }
+
+    #[test]
+    fn rejects_backgrounds_that_are_not_background_materials() {
+        let text = scene_text("", "").replace(r#""background": "sky""#, r#""background": "grey""#);
+        assert_eq!(
+            build_error(&text),
+            "scene.json: `camera.background`: material \"grey\" can't be used as a background, only gradient and solid_color materials can",
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        let text = scene_text("", "");
+        let scene_file = SceneFile::parse(Path::new("scene.json"), &text).unwrap();
+        let parameters = SceneParameters { ambient_occlusion_distance: 0.0, ..test_parameters() };
+        assert_eq!(
+            scene_file.build(parameters).err().unwrap().to_string(),
+            "scene.json: `parameters.ambient_occlusion_distance`: must be positive",
+        );
+    }
+
+    #[test]
+    fn reports_unknown_textures() {
+        let text = scene_text(r#", "wood": { "type": "lambertian", "albedo": "grain" }"#, "");
+        assert_eq!(
+            build_error(&text),
+            "scene.json: `materials.wood.albedo`: no texture named \"grain\" is declared in `textures`",
+        );
+    }
+}
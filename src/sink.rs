@@ -0,0 +1,52 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use crate::framebuffer::Framebuffer;
+use crate::image_format::ImageFormat;
+
+/// Writes rendered images to a file
+pub struct FileSink {
+    path: PathBuf,
+    format: ImageFormat,
+    /// If set, every image is also copied to a timestamped file in this directory,
+    /// to keep a record of progress
+    history_dir: Option<PathBuf>,
+}
+
+impl FileSink {
+    pub fn new(path: &Path, format: ImageFormat) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            format,
+            history_dir: None,
+        }
+    }
+
+    pub fn with_history_dir(mut self, history_dir: &Path) -> Self {
+        self.history_dir = Some(history_dir.to_path_buf());
+        self
+    }
+
+    pub fn write(&self, framebuffer: &Framebuffer) -> io::Result<()> {
+        let encoded = self.format.encode(framebuffer)?;
+        write_creating_dirs(&self.path, &encoded)?;
+
+        if let Some(history_dir) = &self.history_dir {
+            let dist_from_epoch = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).expect("Failed to retrieve time since epoch");
+            let history_path = history_dir.join(format!("{}.{}", dist_from_epoch.as_millis(), self.format.extension()));
+            write_creating_dirs(&history_path, &encoded)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_creating_dirs(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    // Name the file in the error, io errors don't include it
+    fs::write(path, contents).map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))
+}
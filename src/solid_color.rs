@@ -0,0 +1,31 @@
+use std::sync::Arc;
+use crate::color::Color;
+use crate::hittable::HitRecord;
+use crate::material::{Material, ScatterRecord};
+use crate::ray::Ray;
+
+/// A background that's the same color in every direction.
+/// A black background means the only light in the scene comes from emissive materials.
+pub struct SolidColorMaterial {
+    color: Color,
+}
+
+impl SolidColorMaterial {
+    pub fn new(color: Color) -> Arc<Self> {
+        Arc::new(
+            Self {
+                color,
+            }
+        )
+    }
+}
+
+impl Material for SolidColorMaterial {
+    fn scatter(&self, _ray: Ray, _hit_record: &HitRecord) -> Option<ScatterRecord> {
+        None
+    }
+
+    fn background(&self, _ray: Ray) -> Color {
+        self.color
+    }
+}
@@ -1,9 +1,14 @@
+use std::f64::consts::PI;
 use std::sync::Arc;
+use crate::aabb::Aabb;
 use crate::hittable::{HitRecord, Hittable};
 use crate::interval::Interval;
+use crate::light::Light;
 use crate::material::Material;
+use crate::onb::Onb;
 use crate::pos::Pos;
 use crate::ray::Ray;
+use crate::utils::rand_proportion;
 use crate::vec3::Vec3;
 
 #[derive(Clone)]
@@ -11,6 +16,7 @@ pub struct Sphere {
     center: Pos,
     radius: f64,
     material: Arc<dyn Material>,
+    bounding_box: Aabb,
 }
 
 impl Sphere {
@@ -23,46 +29,113 @@ impl Sphere {
             center,
             radius,
             material: Arc::clone(material),
+            bounding_box: sphere_bounding_box(center, radius),
         }
     }
 }
 
+/// Surface coordinates of a point on the unit sphere.
+/// `u` runs around the Y axis starting from -X, and `v` from the bottom pole (-Y) to the top (+Y).
+fn sphere_uv(p: Vec3) -> (f64, f64) {
+    let theta = f64::acos((-p.y).clamp(-1.0, 1.0));
+    let phi = f64::atan2(-p.z, p.x) + PI;
+    (phi / (2.0 * PI), theta / PI)
+}
+
 unsafe impl Send for Sphere {}
 unsafe impl Sync for Sphere {}
 
+/// Intersect a ray with the sphere at `center`, shared by still and moving spheres
+pub fn hit_sphere(
+    center: Pos,
+    radius: f64,
+    material: &Arc<dyn Material>,
+    ray: Ray,
+    ray_t: Interval,
+) -> Option<HitRecord> {
+    let oc = ray.origin - center.into();
+    let a = ray.direction.length_squared();
+    let half_b = Vec3::dot(oc.into(), ray.direction());
+    let c = oc.length_squared() - (radius * radius);
+    let discriminant = (half_b * half_b) - (a * c);
+
+    if discriminant < 0. {
+        return None;
+    }
+
+    let sqrtd = discriminant.sqrt();
+
+    // Find the nearest root (=intersection point) that lies in the acceptable range
+    let mut root = (-half_b - sqrtd) / a;
+    if !ray_t.surrounds(root) {
+        root = (-half_b + sqrtd) / a;
+        if !ray_t.surrounds(root) {
+            return None;
+        }
+    }
+
+    let hit_pos = ray.at(root);
+    let normal = *(hit_pos - center.into()) / radius;
+    // Hollow spheres have inward normals, but should be mapped the same way as solid ones
+    let (u, v) = sphere_uv(*(hit_pos - center.into()) / radius.abs());
+    Some(
+        HitRecord::new(
+            root,
+            hit_pos,
+            ray,
+            normal,
+            material,
+        ).with_uv(u, v)
+    )
+}
+
+/// The box around a sphere. Negative radii are used to model hollow spheres, so bound by the magnitude.
+pub fn sphere_bounding_box(center: Pos, radius: f64) -> Aabb {
+    let extent = Vec3::new(radius.abs(), radius.abs(), radius.abs());
+    Aabb::from_points(center - extent, center + extent)
+}
+
 impl Hittable for Sphere {
     fn hit(&self, ray: Ray, ray_t: Interval) -> Option<HitRecord> {
-        let oc = ray.origin - self.center.into();
-        let a = ray.direction.length_squared();
-        let half_b = Vec3::dot(oc.into(), ray.direction());
-        let c = oc.length_squared() - (self.radius * self.radius);
-        let discriminant = (half_b * half_b) - (a * c);
+        hit_sphere(self.center, self.radius, &self.material, ray, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+}
 
-        if discriminant < 0. {
+impl Sphere {
+    /// Cosine of the half-angle of the cone that the sphere fills, seen from `origin`,
+    /// or `None` from inside the sphere
+    fn cone_cos_theta_max(&self, origin: Pos) -> Option<f64> {
+        let distance_squared = (self.center - *origin).length_squared();
+        let radius_squared = self.radius * self.radius;
+        if distance_squared <= radius_squared {
             return None;
         }
+        Some((1.0 - (radius_squared / distance_squared)).sqrt())
+    }
+}
 
-        let sqrtd = discriminant.sqrt();
+impl Light for Sphere {
+    /// Only directions within the cone the sphere fills are drawn, and all of them equally
+    fn sample_direction(&self, origin: Pos) -> Option<Vec3> {
+        let cos_theta_max = self.cone_cos_theta_max(origin)?;
+        let phi = 2.0 * PI * rand_proportion();
+        let z = 1.0 + (rand_proportion() * (cos_theta_max - 1.0));
+        let sin_theta = (1.0 - (z * z)).max(0.0).sqrt();
+        let to_center = Onb::new(*(self.center - *origin));
+        Some(to_center.local(phi.cos() * sin_theta, phi.sin() * sin_theta, z))
+    }
 
-        // Find the nearest root (=intersection point) that lies in the acceptable range
-        let mut root = (-half_b - sqrtd) / a;
-        if !ray_t.surrounds(root) {
-            root = (-half_b + sqrtd) / a;
-            if !ray_t.surrounds(root) {
-                return None;
-            }
+    fn pdf(&self, origin: Pos, direction: Vec3) -> f64 {
+        let Some(cos_theta_max) = self.cone_cos_theta_max(origin) else {
+            return 0.0;
+        };
+        if self.hit(Ray::new(origin, direction.unit_vector()), Interval::new(0.001, f64::INFINITY)).is_none() {
+            return 0.0;
         }
-
-        let hit_pos = ray.at(root);
-        let normal = *(hit_pos - self.center.into()) / self.radius;
-        Some(
-            HitRecord::new(
-                root,
-                hit_pos,
-                ray,
-                normal,
-                &self.material,
-            )
-        )
+        1.0 / (2.0 * PI * (1.0 - cos_theta_max))
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+use crate::color::Color;
+use crate::pos::Pos;
+
+/// A color that varies across a surface
+pub trait Texture : Send + Sync {
+    /// The color at surface coordinates `(u, v)`, which lie on the surface at `pos`
+    fn value(&self, u: f64, v: f64, pos: Pos) -> Color;
+}
+
+/// The same color everywhere
+pub struct SolidColor {
+    albedo: Color,
+}
+
+impl SolidColor {
+    pub fn new(albedo: Color) -> Arc<Self> {
+        Arc::new(
+            Self {
+                albedo,
+            }
+        )
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _pos: Pos) -> Color {
+        self.albedo
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CheckerMode {
+    /// Checks are cubes filling space, so they're independent of how a surface is parameterized
+    Spatial,
+    /// Checks are squares in surface coordinates, so they follow the surface
+    Uv,
+}
+
+/// Alternates between two textures in a checkerboard pattern
+pub struct CheckerTexture {
+    mode: CheckerMode,
+    inverse_scale: f64,
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+}
+
+impl CheckerTexture {
+    /// `scale` is the width of each check, in world units for spatial checks or surface coordinates for UV checks
+    pub fn new(
+        mode: CheckerMode,
+        scale: f64,
+        even: Arc<dyn Texture>,
+        odd: Arc<dyn Texture>,
+    ) -> Arc<Self> {
+        Arc::new(
+            Self {
+                mode,
+                inverse_scale: 1.0 / scale,
+                even,
+                odd,
+            }
+        )
+    }
+}
+
+impl Texture for CheckerTexture {
+    fn value(&self, u: f64, v: f64, pos: Pos) -> Color {
+        let check = |coordinate: f64| (self.inverse_scale * coordinate).floor() as i64;
+        let sum = match self.mode {
+            CheckerMode::Spatial => check(pos.x) + check(pos.y) + check(pos.z),
+            CheckerMode::Uv => check(u) + check(v),
+        };
+        if sum.rem_euclid(2) == 0 {
+            self.even.value(u, v, pos)
+        }
+        else {
+            self.odd.value(u, v, pos)
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::matrix4::Matrix4;
+use crate::pos::Pos;
+use crate::ray::Ray;
+
+/// Places an object in the world through an affine transformation.
+/// The object is shared rather than copied, so many instances of one object cost little more than one.
+pub struct Transform {
+    object: Arc<dyn Hittable>,
+    object_to_world: Matrix4,
+    world_to_object: Matrix4,
+    /// Carries normals out of object space, keeping them perpendicular to the transformed surface
+    normal_to_world: Matrix4,
+    bounding_box: Aabb,
+}
+
+impl Transform {
+    /// Returns `None` if the transformation can't be undone, such as a scale by zero
+    pub fn new(object: Arc<dyn Hittable>, object_to_world: Matrix4) -> Option<Self> {
+        let world_to_object = object_to_world.inverse()?;
+        let object_box = object.bounding_box();
+        let bounding_box = if object_box.is_unbounded() {
+            Aabb::unbounded()
+        }
+        else {
+            // The transformed box's corners enclose the transformed object
+            (0..8).fold(Aabb::empty(), |bounding_box, corner| {
+                let pick = |axis: usize, interval: Interval| if corner & (1 << axis) == 0 { interval.min } else { interval.max };
+                let corner = object_to_world.transform_point(Pos::new(
+                    pick(0, object_box.x),
+                    pick(1, object_box.y),
+                    pick(2, object_box.z),
+                ));
+                Aabb::enclosing(&bounding_box, &Aabb::from_points(corner, corner))
+            })
+        };
+        Some(Self {
+            object,
+            object_to_world,
+            world_to_object,
+            normal_to_world: world_to_object.transpose(),
+            bounding_box,
+        })
+    }
+}
+
+impl Hittable for Transform {
+    fn hit(&self, ray: Ray, ray_t: Interval) -> Option<HitRecord> {
+        // Leaving the direction unnormalized keeps `t` the same in both spaces
+        let object_ray = Ray::with_time(
+            self.world_to_object.transform_point(ray.origin),
+            self.world_to_object.transform_vector(ray.direction),
+            ray.time,
+        );
+        let mut hit_record = self.object.hit(object_ray, ray_t)?;
+        hit_record.pos = self.object_to_world.transform_point(hit_record.pos);
+        // The normal already faces against the ray, and the inverse transpose preserves that
+        hit_record.normal = self.normal_to_world.transform_vector(hit_record.normal).unit_vector();
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+}
@@ -0,0 +1,127 @@
+use std::sync::Arc;
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::pos::Pos;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+/// Surface coordinates at a vertex
+pub type Uv = (f64, f64);
+
+/// Möller–Trumbore ray/triangle intersection.
+/// Returns the ray parameter of the hit, and the weights of the second and third vertices there.
+pub fn intersect_triangle(ray: Ray, vertices: [Pos; 3], ray_t: Interval) -> Option<(f64, f64, f64)> {
+    let edge1 = vertices[1] - *vertices[0];
+    let edge2 = vertices[2] - *vertices[0];
+    let p = Vec3::cross(&ray.direction, &edge2);
+    let determinant = Vec3::dot(*edge1, p);
+    // The ray is parallel to the triangle's plane
+    if determinant.abs() < 1e-12 {
+        return None;
+    }
+    let inverse_determinant = 1.0 / determinant;
+
+    let to_origin = ray.origin - *vertices[0];
+    let b1 = Vec3::dot(*to_origin, p) * inverse_determinant;
+    if !(0.0..=1.0).contains(&b1) {
+        return None;
+    }
+
+    let q = Vec3::cross(&to_origin, &edge1);
+    let b2 = Vec3::dot(ray.direction, q) * inverse_determinant;
+    if b2 < 0.0 || b1 + b2 > 1.0 {
+        return None;
+    }
+
+    let t = Vec3::dot(*edge2, q) * inverse_determinant;
+    if !ray_t.surrounds(t) {
+        return None;
+    }
+    Some((t, b1, b2))
+}
+
+/// Build the hit record for a triangle intersection found by `intersect_triangle`,
+/// interpolating any per-vertex normals and surface coordinates.
+/// Without vertex surface coordinates, the barycentric coordinates are used instead.
+pub fn triangle_hit_record(
+    ray: Ray,
+    vertices: [Pos; 3],
+    normals: Option<[Vec3; 3]>,
+    uvs: Option<[Uv; 3]>,
+    (t, b1, b2): (f64, f64, f64),
+    material: &Arc<dyn Material>,
+) -> HitRecord {
+    let weights = [1.0 - b1 - b2, b1, b2];
+    // Counter-clockwise winding faces outwards
+    let geometric_normal = Vec3::cross(&(vertices[1] - *vertices[0]), &(vertices[2] - *vertices[0])).unit_vector();
+    let mut hit_record = HitRecord::new(t, ray.at(t), ray, geometric_normal, material).with_barycentric(weights);
+
+    if let Some(normals) = normals {
+        let interpolated = (weights[0] * normals[0]) + (weights[1] * normals[1]) + (weights[2] * normals[2]);
+        hit_record = hit_record.with_shading_normal(interpolated.unit_vector());
+    }
+
+    match uvs {
+        Some(uvs) => {
+            let u = (weights[0] * uvs[0].0) + (weights[1] * uvs[1].0) + (weights[2] * uvs[2].0);
+            let v = (weights[0] * uvs[0].1) + (weights[1] * uvs[1].1) + (weights[2] * uvs[2].1);
+            hit_record.with_uv(u, v)
+        }
+        None => hit_record.with_uv(b1, b2),
+    }
+}
+
+pub fn triangle_bounding_box(vertices: [Pos; 3]) -> Aabb {
+    Aabb::enclosing(
+        &Aabb::from_points(vertices[0], vertices[1]),
+        &Aabb::from_points(vertices[2], vertices[2]),
+    ).padded()
+}
+
+pub struct Triangle {
+    vertices: [Pos; 3],
+    normals: Option<[Vec3; 3]>,
+    uvs: Option<[Uv; 3]>,
+    material: Arc<dyn Material>,
+    bounding_box: Aabb,
+}
+
+impl Triangle {
+    /// Vertices should be given counter-clockwise, seen from the front
+    pub fn new(
+        vertices: [Pos; 3],
+        material: &Arc<dyn Material>,
+    ) -> Self {
+        Self {
+            vertices,
+            normals: None,
+            uvs: None,
+            material: Arc::clone(material),
+            bounding_box: triangle_bounding_box(vertices),
+        }
+    }
+
+    /// Shade with normals interpolated from these per-vertex normals
+    pub fn with_normals(mut self, normals: [Vec3; 3]) -> Self {
+        self.normals = Some(normals);
+        self
+    }
+
+    pub fn with_uvs(mut self, uvs: [Uv; 3]) -> Self {
+        self.uvs = Some(uvs);
+        self
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, ray: Ray, ray_t: Interval) -> Option<HitRecord> {
+        let intersection = intersect_triangle(ray, self.vertices, ray_t)?;
+        Some(triangle_hit_record(ray, self.vertices, self.normals, self.uvs, intersection, &self.material))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounding_box
+    }
+}
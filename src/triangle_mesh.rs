@@ -0,0 +1,118 @@
+use std::sync::Arc;
+use crate::aabb::Aabb;
+use crate::bvh::BvhNode;
+use crate::hittable::{HitRecord, Hittable};
+use crate::hittable_list::HittableList;
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::pos::Pos;
+use crate::ray::Ray;
+use crate::triangle::{intersect_triangle, triangle_bounding_box, triangle_hit_record, Uv};
+use crate::vec3::Vec3;
+
+/// A triangle of a mesh, as indexes into the mesh's vertex buffers.
+/// Each attribute is indexed separately, so vertices can share positions but not normals (as at a hard edge).
+#[derive(Debug, Copy, Clone)]
+pub struct MeshFace {
+    pub positions: [usize; 3],
+    pub normals: Option<[usize; 3]>,
+    pub uvs: Option<[usize; 3]>,
+}
+
+/// Vertex buffers and faces, shared by every triangle of the mesh
+pub struct MeshData {
+    pub positions: Vec<Pos>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<Uv>,
+    pub faces: Vec<MeshFace>,
+    pub material: Arc<dyn Material>,
+}
+
+impl MeshData {
+    fn vertices(&self, face: &MeshFace) -> [Pos; 3] {
+        face.positions.map(|i| self.positions[i])
+    }
+}
+
+/// One face of a mesh, looking up its vertices in the shared buffers
+struct MeshTriangle {
+    mesh: Arc<MeshData>,
+    face: usize,
+}
+
+impl Hittable for MeshTriangle {
+    fn hit(&self, ray: Ray, ray_t: Interval) -> Option<HitRecord> {
+        let face = &self.mesh.faces[self.face];
+        let vertices = self.mesh.vertices(face);
+        let intersection = intersect_triangle(ray, vertices, ray_t)?;
+        Some(triangle_hit_record(
+            ray,
+            vertices,
+            face.normals.map(|indexes| indexes.map(|i| self.mesh.normals[i])),
+            face.uvs.map(|indexes| indexes.map(|i| self.mesh.uvs[i])),
+            intersection,
+            &self.mesh.material,
+        ))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        triangle_bounding_box(self.mesh.vertices(&self.mesh.faces[self.face]))
+    }
+}
+
+/// Many triangles sharing vertex buffers and a material.
+/// The mesh keeps its own bounding volume hierarchy over its triangles,
+/// so it can be added to a world as a single object.
+pub struct TriangleMesh {
+    triangles: BvhNode,
+}
+
+#[derive(Debug)]
+pub struct MeshError(pub String);
+
+impl std::fmt::Display for MeshError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for MeshError {}
+
+impl TriangleMesh {
+    pub fn new(data: MeshData) -> Result<Self, MeshError> {
+        // Check every index up-front, so hit testing can't go out of bounds
+        for (i, face) in data.faces.iter().enumerate() {
+            let check = |indexes: Option<[usize; 3]>, len: usize, buffer: &str| {
+                match indexes.and_then(|indexes| indexes.into_iter().find(|index| *index >= len)) {
+                    Some(index) => Err(MeshError(format!("face {i} refers to {buffer} {index}, but there are only {len}"))),
+                    None => Ok(()),
+                }
+            };
+            check(Some(face.positions), data.positions.len(), "position")?;
+            check(face.normals, data.normals.len(), "normal")?;
+            check(face.uvs, data.uvs.len(), "uv")?;
+        }
+
+        let data = Arc::new(data);
+        let mut triangles = HittableList::new();
+        for face in 0..data.faces.len() {
+            triangles.add(Box::new(MeshTriangle {
+                mesh: Arc::clone(&data),
+                face,
+            }));
+        }
+        Ok(Self {
+            triangles: BvhNode::new(triangles),
+        })
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, ray: Ray, ray_t: Interval) -> Option<HitRecord> {
+        self.triangles.hit(ray, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.triangles.bounding_box()
+    }
+}
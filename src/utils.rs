@@ -1,15 +1,32 @@
+use std::cell::RefCell;
 use std::f64::consts::PI;
 use rand;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+thread_local! {
+    /// Each thread draws from its own generator, which starts out randomly seeded.
+    /// Re-seeding it makes everything drawn afterwards on this thread reproducible.
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::seed_from_u64(rand::thread_rng().gen()));
+}
 
 pub fn degrees_to_radians(degrees: f64) -> f64 {
     degrees * PI / 180.
 }
 
+pub fn seed_rng(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// Run `f` with this thread's random number generator
+pub fn with_rng<T>(f: impl FnOnce(&mut StdRng) -> T) -> T {
+    RNG.with(|rng| f(&mut rng.borrow_mut()))
+}
+
 pub fn rand_proportion() -> f64 {
-    rand::thread_rng().gen_range(0.0..1.0)
+    with_rng(|rng| rng.gen_range(0.0..1.0))
 }
 
 pub fn rand_double(min: f64, max: f64) -> f64 {
-    rand::thread_rng().gen_range(min..max)
+    with_rng(|rng| rng.gen_range(min..max))
 }
@@ -1,3 +1,4 @@
+use std::f64::consts::PI;
 use std::fmt::{Display, Formatter};
 use std::ops::{Add, AddAssign, Deref, Div, DivAssign, Index, Mul, MulAssign, Neg, Sub};
 use crate::utils::{rand_double, rand_proportion};
@@ -47,6 +48,16 @@ impl Vec3 {
         Self::random_in_unit_sphere().unit_vector()
     }
 
+    /// A random direction above the XY plane, more likely the closer it is to +Z,
+    /// with a probability density of `z / π`
+    pub fn random_cosine_direction() -> Self {
+        let r1 = rand_proportion();
+        let r2 = rand_proportion();
+        let phi = 2.0 * PI * r1;
+        let sqrt_r2 = r2.sqrt();
+        Vec3::new(phi.cos() * sqrt_r2, phi.sin() * sqrt_r2, (1.0 - r2).sqrt())
+    }
+
     pub fn random_in_unit_disk() -> Self {
         loop {
             let v = Vec3::new(
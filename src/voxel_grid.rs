@@ -0,0 +1,114 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::vec3::Vec3;
+
+#[derive(Debug)]
+pub enum GridError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Decode {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl Display for GridError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridError::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            GridError::Decode { path, message } => write!(f, "{}: {message}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+/// A dense 3D grid of values, such as the density of a cloud, read from a file laid out as:
+///
+/// ```text
+/// DENSEGRID <width> <height> <depth>
+/// <width * height * depth little-endian f32 values, x varying fastest, then y, then z>
+/// ```
+pub struct VoxelGrid {
+    resolution: [usize; 3],
+    values: Vec<f64>,
+    max_value: f64,
+}
+
+impl VoxelGrid {
+    pub fn load(path: &Path) -> Result<Self, GridError> {
+        let bytes = fs::read(path).map_err(|source| GridError::Io { path: path.to_path_buf(), source })?;
+        Self::decode(&bytes).map_err(|message| GridError::Decode { path: path.to_path_buf(), message })
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let header_end = bytes.iter().position(|b| *b == b'\n').ok_or("missing header")?;
+        let header = std::str::from_utf8(&bytes[..header_end]).map_err(|_| "header is not text")?;
+        let mut fields = header.split_whitespace();
+        if fields.next() != Some("DENSEGRID") {
+            return Err("not a dense grid, which starts with `DENSEGRID`".to_string());
+        }
+        let mut dimension = || -> Result<usize, String> {
+            let field = fields.next().ok_or("header needs a width, height and depth")?;
+            match field.parse() {
+                Ok(size) if size > 0 => Ok(size),
+                _ => Err(format!("invalid grid size \"{field}\"")),
+            }
+        };
+        let resolution = [dimension()?, dimension()?, dimension()?];
+
+        let data = &bytes[header_end + 1..];
+        let count = resolution.iter().try_fold(1usize, |count, size| count.checked_mul(*size)).ok_or("grid is too large")?;
+        if Some(data.len()) != count.checked_mul(4) {
+            return Err(format!("expected {count} values, but found {} bytes of data", data.len()));
+        }
+        let values: Vec<f64> = data.chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) as f64)
+            .collect();
+        if let Some(bad) = values.iter().find(|value| !value.is_finite() || **value < 0.0) {
+            return Err(format!("values must be finite and not negative, but found {bad}"));
+        }
+        let max_value = values.iter().copied().fold(0.0, f64::max);
+        Ok(Self {
+            resolution,
+            values,
+            max_value,
+        })
+    }
+
+    /// No sample is larger than this
+    pub fn max_value(&self) -> f64 {
+        self.max_value
+    }
+
+    fn value(&self, x: usize, y: usize, z: usize) -> f64 {
+        let [width, height, _] = self.resolution;
+        self.values[x + (width * (y + (height * z)))]
+    }
+
+    /// Trilinearly interpolate between voxel centers, where each coordinate of `p` runs from 0 to 1 across the grid.
+    /// Beyond the outermost centers, the edge voxels extend outwards.
+    pub fn sample(&self, p: Vec3) -> f64 {
+        // The lower of the two voxels to blend along each axis, and how far towards the upper one
+        let axis = |axis: usize, coordinate: f64| {
+            let size = self.resolution[axis];
+            let position = ((coordinate * size as f64) - 0.5).clamp(0.0, (size - 1) as f64);
+            let lower = (position as usize).min(size.saturating_sub(2));
+            (lower, (lower + 1).min(size - 1), position - lower as f64)
+        };
+        let (x0, x1, tx) = axis(0, p.x);
+        let (y0, y1, ty) = axis(1, p.y);
+        let (z0, z1, tz) = axis(2, p.z);
+
+        let lerp = |a: f64, b: f64, t: f64| a + ((b - a) * t);
+        let plane = |z: usize| lerp(
+            lerp(self.value(x0, y0, z), self.value(x1, y0, z), tx),
+            lerp(self.value(x0, y1, z), self.value(x1, y1, z), tx),
+            ty,
+        );
+        lerp(plane(z0), plane(z1), tz)
+    }
+}